@@ -38,37 +38,27 @@ fn main() -> Result<()> {
         lstrs.push(lstr);
     });
 
-    let mut lstrs2: Vec<LineString> = vec![];
-    for i in 1..lstrs.len() - 1 {
-        let mut segments: Vec<LineString> = vec![lstrs[i].clone()];
-        for other in lstrs.iter().take(lstrs.len() - 1).skip(i + 1) {
-            let mut points: Vec<Vec2> = other.points.clone();
+    // Each wave hides the area below it from the waves above, which are
+    // further back
+    let occluders = lstrs
+        .iter()
+        .rev()
+        .map(|l| {
+            let mut points = l.points.clone();
             let first = points[0];
             let last = points[points.len() - 1];
-            points.insert(
-                0,
-                Vec2 {
-                    x: first.x,
-                    y: -1000.,
-                },
-            );
             points.push(Vec2 {
                 x: last.x,
-                y: -1000.,
+                y: 10000.,
             });
-            let other: LineString = LineString::new(points);
-            let mut newsegments: Vec<LineString> = vec![];
-            segments.iter().for_each(|s| {
-                let subs = s.clip(&other, false);
-                subs.iter().for_each(|subseg| {
-                    newsegments.push(subseg.clone());
-                });
+            points.push(Vec2 {
+                x: first.x,
+                y: 10000.,
             });
-            segments.clone_from(&newsegments);
-        }
-        segments.iter().for_each(|s| lstrs2.push(s.clone()));
-    }
-    lstrs2.push(lstrs.last().unwrap().clone());
+            Occluder::new(Polygon::new(points), vec![l.clone()])
+        })
+        .collect::<Vec<Occluder>>();
+    let lstrs2: Vec<LineString> = occlude(&occluders).into_iter().flatten().collect();
 
     let mut lstrs3: Vec<LineString> = vec![];
     lstrs2.iter().for_each(|l| {
@@ -66,7 +66,7 @@ fn main() -> Result<()> {
             t_max,
         );
         let t = if clockwise { t } else { 1. - t };
-        let t_step = if clockwise { t_step } else { t_step * -1. };
+        let t_step = if clockwise { t_step } else { -t_step };
         pursuit_polygons_times(&polygon, t, t_step, times)
             .iter()
             .for_each(|p| shapes.push(p.clone()));
@@ -1,10 +1,7 @@
 use anyhow::Result;
+use plt::field::Scalar2;
 use plt::prelude::*;
 
-fn sdf_circle(p: Vec2, circle: &Circle) -> f64 {
-    p.distance(circle.center) - circle.radius
-}
-
 fn main() -> Result<()> {
     let seed: u64 = 200;
     let poisson_radius: f64 = 4.5;
@@ -29,7 +26,7 @@ fn main() -> Result<()> {
     let center = sketch.center();
 
     for point in &distribution1 {
-        let dist = sdf_circle(*point, &circle);
+        let dist = circle.number2(*point);
         let dist_abs = dist.abs() / 3.;
         sketch.group(0).add(Circle::new(
             point.centroid(),
@@ -41,7 +38,7 @@ fn main() -> Result<()> {
     }
 
     for point in &distribution2 {
-        let dist = sdf_circle(*point, &circle);
+        let dist = circle.number2(*point);
         let dist_abs = dist.abs() / 6.;
         sketch.group(1).add(Circle::new(
             point.centroid(),
@@ -1,10 +1,7 @@
 use anyhow::Result;
+use plt::field::Scalar2;
 use plt::prelude::*;
 
-fn sdf_circle(p: Vec2, circle: &Circle) -> f64 {
-    p.distance(circle.center) - circle.radius
-}
-
 fn main() -> Result<()> {
     let seed: u64 = 123;
     let poisson_radius: f64 = 5.0;
@@ -28,7 +25,7 @@ fn main() -> Result<()> {
     let circle = Circle::new(sketch.center() + Vec2::new(35., 50.), 14.0);
 
     for point in &distribution1 {
-        let dist = sdf_circle(*point, &circle);
+        let dist = circle.number2(*point);
         let dist_abs = dist.abs() / 3.;
         sketch.group(0).add(Rect::square_with_center(
             point.centroid(),
@@ -37,7 +34,7 @@ fn main() -> Result<()> {
     }
 
     for point in &distribution2 {
-        let dist = sdf_circle(*point, &circle);
+        let dist = circle.number2(*point);
         let dist_abs = dist.abs() / 6.;
         sketch.group(1).add(Rect::square_with_center(
             point.centroid(),
@@ -12,19 +12,19 @@ fn truchet(hexagon: &Polygon) -> Vec<LineString> {
         let p3 = hexagon.points[(i + 2) % 6];
         let p12 = p1.lerp(p2, 0.5);
         let p23 = p2.lerp(p3, 0.5);
-        let angle_start = p2.angle_between(p12);
-        let mut angle_end = p2.angle_between(p23);
-        if f64::abs(angle_start.to_degrees() - angle_end.to_degrees()) > 180. {
-            angle_end = Angle::from_degrees(angle_end.to_degrees() - 360.);
+        let mut arc = Arc::new(
+            p2,
+            p23.distance(p2),
+            p2.angle_between(p12),
+            p2.angle_between(p23),
+            Direction::Clockwise,
+        );
+        if arc.sweep() > PI {
+            arc.direction = Direction::CounterClockwise;
         }
         for offset in (-8..=8).step_by(2) {
-            let mut points: Vec<Vec2> = vec![];
-            for step in 0..=20 {
-                let angle_step = angle_start.lerp(angle_end, step as f64 / 20.);
-                points.push(p2 + Vec2::from_polar(angle_step, p23.distance(p2) + offset as f64));
-            }
-            let line = LineString::new(points);
-            linestrings.push(line);
+            arc.radius = p23.distance(p2) + offset as f64;
+            linestrings.push(arc.to_linestring(0.05));
         }
     }
     linestrings
@@ -109,17 +109,21 @@ fn main() -> Result<()> {
         .for_each(|l| segments.extend_from_slice(&l.clip(&bbox3, false)));
     sketch.group(2).add_many(segments);
 
-    // TODO: it is clear that scale_dist do not always infer what is the interior
-    // and what is the exterior of a polygon... BUG!
-    sketch.group(3).add(bbox1.scale_dist(0.));
-    sketch.group(3).add(bbox1.scale_dist(-1.));
-    sketch.group(3).add(bbox1.scale_dist(-2.));
-    sketch.group(3).add(bbox2.scale_dist(0.));
-    sketch.group(3).add(bbox2.scale_dist(1.));
-    sketch.group(3).add(bbox2.scale_dist(2.));
-    sketch.group(3).add(bbox3.scale_dist(0.));
-    sketch.group(3).add(bbox3.scale_dist(1.));
-    sketch.group(3).add(bbox3.scale_dist(2.));
+    // bbox1 is wound the other way round, so it shrinks with negative distances
+    let outlines = [
+        bbox1.scale_dist(0.),
+        bbox1.scale_dist(-1.),
+        bbox1.scale_dist(-2.),
+        bbox2.scale_dist(0.),
+        bbox2.scale_dist(1.),
+        bbox2.scale_dist(2.),
+        bbox3.scale_dist(0.),
+        bbox3.scale_dist(1.),
+        bbox3.scale_dist(2.),
+    ];
+    sketch
+        .group(3)
+        .add_many(outlines.into_iter().flatten().collect());
 
     sketch.group(0).set_style(Style::new("black", "1.0px"));
     sketch.group(1).set_style(Style::new("black", "1.8px"));
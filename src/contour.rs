@@ -0,0 +1,272 @@
+//! Iso-contours of scalar fields, traced with marching squares
+
+use std::collections::BTreeMap;
+
+use crate::field::Scalar2;
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::shapes::rectangle::Rect;
+use crate::vec2::Vec2;
+
+/// A side of a grid cell: the horizontal one going right from the sample
+/// `(column, row)`, or the vertical one going down from it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Side {
+    Horizontal(usize, usize),
+    Vertical(usize, usize),
+}
+
+/// The settings of iso-contours traced over a grid of samples of a field.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Contours {
+    /// The area sampled
+    pub bounds: Rect,
+    /// The largest distance between neighbouring samples
+    pub spacing: f64,
+}
+
+/// The contours of a field at one of the thresholds.
+#[derive(Clone, PartialEq)]
+pub struct Isolines {
+    pub threshold: f64,
+    /// The contours ending on the bounds
+    pub open: Vec<LineString>,
+    /// The contours looping inside the bounds
+    pub closed: Vec<Polygon>,
+}
+
+impl Contours {
+    pub fn new(bounds: Rect, spacing: f64) -> Self {
+        Self { bounds, spacing }
+    }
+
+    /// Return the contours of `field` for each of the `thresholds`, in the
+    /// same order. Values are interpolated linearly along the sides of the
+    /// cells, and saddle cells are settled by the mean of their corners.
+    pub fn isolines<F: Scalar2>(&self, field: &F, thresholds: &[f64]) -> Vec<Isolines> {
+        if self.spacing <= 0. {
+            return vec![];
+        }
+        let Rect { xy, width, height } = self.bounds;
+        let columns = (width / self.spacing).ceil().max(1.) as usize;
+        let rows = (height / self.spacing).ceil().max(1.) as usize;
+        let position = |column: usize, row: usize| {
+            xy + Vec2::new(
+                width * column as f64 / columns as f64,
+                height * row as f64 / rows as f64,
+            )
+        };
+        let samples = (0..=rows)
+            .map(|row| {
+                (0..=columns)
+                    .map(|column| field.number2(position(column, row)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<f64>>>();
+
+        thresholds
+            .iter()
+            .map(|&threshold| {
+                let crossing = |side: Side| {
+                    let ((c0, r0), (c1, r1)) = match side {
+                        Side::Horizontal(c, r) => ((c, r), (c + 1, r)),
+                        Side::Vertical(c, r) => ((c, r), (c, r + 1)),
+                    };
+                    let (a, b) = (samples[r0][c0], samples[r1][c1]);
+                    let t = (threshold - a) / (b - a);
+                    position(c0, r0) + (position(c1, r1) - position(c0, r0)) * t
+                };
+                let mut links = BTreeMap::<Side, Vec<Side>>::new();
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let cell = [
+                            samples[row][column],
+                            samples[row][column + 1],
+                            samples[row + 1][column + 1],
+                            samples[row + 1][column],
+                        ];
+                        for (a, b) in segments(cell, threshold, column, row) {
+                            links.entry(a).or_default().push(b);
+                            links.entry(b).or_default().push(a);
+                        }
+                    }
+                }
+                // Sides meeting at a sample equal to the threshold cross it at
+                // the same point
+                let points = |sides: Vec<Side>| {
+                    let mut points = sides.into_iter().map(crossing).collect::<Vec<Vec2>>();
+                    points.dedup();
+                    points
+                };
+                let (open, closed) = chain(links);
+                Isolines {
+                    threshold,
+                    open: open
+                        .into_iter()
+                        .map(points)
+                        .filter(|p| p.len() > 1)
+                        .map(LineString::new)
+                        .collect(),
+                    closed: closed
+                        .into_iter()
+                        .map(|sides| {
+                            let mut ring = points(sides);
+                            if ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+                                ring.pop();
+                            }
+                            ring
+                        })
+                        .filter(|r| r.len() > 2)
+                        .map(Polygon::new)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Return the pairs of sides of a cell joined by the contour at `threshold`,
+/// given the values at its corners clockwise from the top left one.
+fn segments(cell: [f64; 4], threshold: f64, column: usize, row: usize) -> Vec<(Side, Side)> {
+    if cell.iter().any(|v| v.is_nan()) {
+        return vec![];
+    }
+    // Side `i` goes from corner `i` to the next one
+    let sides = [
+        Side::Horizontal(column, row),
+        Side::Vertical(column + 1, row),
+        Side::Horizontal(column, row + 1),
+        Side::Vertical(column, row),
+    ];
+    let above = cell.map(|v| v >= threshold);
+    let crossed = (0..4)
+        .filter(|&i| above[i] != above[(i + 1) % 4])
+        .collect::<Vec<usize>>();
+    match crossed.len() {
+        2 => vec![(sides[crossed[0]], sides[crossed[1]])],
+        4 => {
+            // Opposite corners match: cut off the two corners on the other
+            // side of the threshold from the center
+            let center = cell.iter().sum::<f64>() / 4. >= threshold;
+            let first = if center == above[0] { 1 } else { 0 };
+            let cut = |corner: usize| (sides[(corner + 3) % 4], sides[corner]);
+            vec![cut(first), cut(first + 2)]
+        }
+        _ => vec![],
+    }
+}
+
+/// Chain the sides linked by segments into paths, the open ones, starting
+/// and ending at sides with a single link, and the closed ones.
+fn chain(mut links: BTreeMap<Side, Vec<Side>>) -> (Vec<Vec<Side>>, Vec<Vec<Side>>) {
+    fn follow(start: Side, links: &mut BTreeMap<Side, Vec<Side>>) -> Vec<Side> {
+        let mut path = vec![start];
+        let mut current = start;
+        while let Some(next) = links.get_mut(&current).and_then(|l| l.pop()) {
+            let back = links.get_mut(&next).unwrap();
+            back.remove(back.iter().position(|s| *s == current).unwrap());
+            path.push(next);
+            current = next;
+        }
+        path
+    }
+
+    let ends = links
+        .iter()
+        .filter(|(_, l)| l.len() == 1)
+        .map(|(s, _)| *s)
+        .collect::<Vec<Side>>();
+    let mut open = vec![];
+    for end in ends {
+        if links[&end].len() == 1 {
+            open.push(follow(end, &mut links));
+        }
+    }
+    let mut closed = vec![];
+    while let Some(start) = links.iter().find(|(_, l)| !l.is_empty()).map(|(s, _)| *s) {
+        let mut ring = follow(start, &mut links);
+        ring.pop();
+        closed.push(ring);
+    }
+    (open, closed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contour::Contours;
+    use crate::prelude::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn circles_are_closed_contours() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let center = Vec2::new(50., 50.);
+        let distance = |p: Vec2| p.distance(center);
+        let isolines = Contours::new(bounds, 1.).isolines(&distance, &[10., 20.]);
+        assert_eq!(isolines.len(), 2);
+        for lines in isolines {
+            assert!(lines.open.is_empty());
+            assert_eq!(lines.closed.len(), 1);
+            let ring = &lines.closed[0].points;
+            assert!(ring.len() > 40);
+            assert!(ring
+                .iter()
+                .all(|p| (p.distance(center) - lines.threshold).abs() < 0.05));
+            // Consecutive points are neighbours, the ring is not scrambled
+            let last = ring[ring.len() - 1];
+            assert!(ring.windows(2).all(|w| w[0].distance(w[1]) < 1.5));
+            assert!(last.distance(ring[0]) < 1.5);
+        }
+    }
+
+    #[test]
+    fn contours_end_on_the_bounds() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let slope = |p: Vec2| p.x;
+        let isolines = Contours::new(bounds, 1.).isolines(&slope, &[2.5]);
+        assert!(isolines[0].closed.is_empty());
+        assert_eq!(isolines[0].open.len(), 1);
+        let points = &isolines[0].open[0].points;
+        assert_eq!(points.len(), 11);
+        assert!(points.iter().all(|p| p.x == 2.5));
+        assert_relative_eq!((points[0].y - points[10].y).abs(), 10.);
+    }
+
+    #[test]
+    fn saddles_follow_the_center() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 2., 2.);
+        // 1 at the top left and bottom right corners, -1 at the others
+        let saddle = |p: Vec2| (p.x - 1.) * (p.y - 1.);
+        let lines = Contours::new(bounds, 2.).isolines(&saddle, &[0.5, -0.5]);
+        // Above the center, the contours cut off the top left and bottom
+        // right corners
+        assert_eq!(lines[0].open.len(), 2);
+        for line in &lines[0].open {
+            let middle = (line.points[0] + line.points[1]) / 2.;
+            assert!(middle.distance(Vec2::new(1., 1.)) > 0.5);
+            assert!((middle.x - middle.y).abs() < 1e-9);
+        }
+        // Below, the other two
+        assert_eq!(lines[1].open.len(), 2);
+        for line in &lines[1].open {
+            let middle = (line.points[0] + line.points[1]) / 2.;
+            assert_relative_eq!(middle.x + middle.y, 2.);
+        }
+    }
+
+    #[test]
+    fn thresholds_on_samples_make_no_empty_segments() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 20., 20.);
+        let steps = |p: Vec2| (p.x.floor() + p.y.floor()) % 3.;
+        let isolines = Contours::new(bounds, 1.).isolines(&steps, &[1.]);
+        let rings = isolines[0].closed.iter().map(|p| {
+            let mut ring = p.points.clone();
+            ring.push(ring[0]);
+            ring
+        });
+        let lines = isolines[0].open.iter().map(|l| l.points.clone());
+        let paths = lines.chain(rings).collect::<Vec<Vec<Vec2>>>();
+        assert!(!paths.is_empty());
+        assert!(paths.iter().all(|p| p.windows(2).all(|w| w[0] != w[1])));
+    }
+}
@@ -8,6 +8,9 @@ use crate::vec3::Vec3;
 use noise::NoiseFn;
 use noise::Perlin;
 
+pub mod combinators;
+pub mod fractal;
+
 pub trait Scalar2 {
     fn number2(&self, pos: Vec2) -> f64;
     fn angle2(&self, pos: Vec2, increments: f64) -> Angle;
@@ -26,6 +29,39 @@ pub trait Vector3to2 {
     fn vec3(&self, pos: Vec3) -> Vec2;
 }
 
+// Closures are fields too, mapping positions to values or vectors
+impl<F: Fn(Vec2) -> f64> Scalar2 for F {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self(pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self(pos), increment)
+    }
+}
+
+impl<F: Fn(Vec3) -> f64> Scalar3 for F {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self(pos)
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self(pos), increment)
+    }
+}
+
+impl<F: Fn(Vec2) -> Vec2> Vector2to2 for F {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        self(pos)
+    }
+}
+
+impl<F: Fn(Vec3) -> Vec2> Vector3to2 for F {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        self(pos)
+    }
+}
+
 pub struct PerlinField {
     noise_fn: Perlin,
 }
@@ -44,12 +80,7 @@ impl Scalar2 for PerlinField {
     }
 
     fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
-        let noise_val = self.number2(pos);
-        if increment == 0. {
-            return Angle::from_radians(map_range(noise_val, -1., 1., 0., TAU));
-        }
-        let angle = PI * noise_val;
-        Angle::from_radians((angle / increment).round() * increment)
+        value_to_angle(self.number2(pos), increment)
     }
 }
 
@@ -59,28 +90,19 @@ impl Scalar3 for PerlinField {
     }
 
     fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
-        let noise_val = self.number3(pos);
-        if increment == 0. {
-            return Angle::from_radians(map_range(noise_val, -1., 1., 0., TAU));
-        }
-        let angle = PI * noise_val;
-        Angle::from_radians((angle / increment).round() * increment)
+        value_to_angle(self.number3(pos), increment)
     }
 }
 
 impl Vector2to2 for PerlinField {
     fn vec2(&self, pos: Vec2) -> Vec2 {
-        let noise_val = self.number2(pos);
-        let angle = Angle::from_radians(map_range(noise_val, -1., 1., 0., TAU));
-        Vec2::from_polar(angle, 1.)
+        Vec2::from_polar(self.angle2(pos, 0.), 1.)
     }
 }
 
 impl Vector3to2 for PerlinField {
     fn vec3(&self, pos: Vec3) -> Vec2 {
-        let noise_val = self.number3(pos);
-        let angle = Angle::from_radians(map_range(noise_val, -1., 1., 0., TAU));
-        Vec2::from_polar(angle, 1.)
+        Vec2::from_polar(self.angle3(pos, 0.), 1.)
     }
 }
 
@@ -102,12 +124,7 @@ impl Scalar2 for SpiralField {
     }
 
     fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
-        let noise_val = self.number2(pos);
-        if increment == 0. {
-            return Angle::from_radians(map_range(noise_val, -1., 1., 0., TAU));
-        }
-        let angle = PI * noise_val;
-        Angle::from_radians((angle / increment).round() * increment)
+        value_to_angle(self.number2(pos), increment)
     }
 }
 
@@ -119,3 +136,14 @@ impl Vector2to2 for SpiralField {
         Vec2::from_polar(Angle::from_radians(angle), 0.5)
     }
 }
+
+/// Map a field value between -1 and 1 to an angle: over a full turn when
+/// `increment` is 0, or over half turns either way, rounded to multiples of
+/// `increment`, otherwise.
+pub(crate) fn value_to_angle(value: f64, increment: f64) -> Angle {
+    if increment == 0. {
+        return Angle::from_radians(map_range(value, -1., 1., 0., TAU));
+    }
+    let angle = PI * value;
+    Angle::from_radians((angle / increment).round() * increment)
+}
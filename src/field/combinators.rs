@@ -0,0 +1,431 @@
+use crate::angle::Angle;
+use crate::field::{value_to_angle, Scalar2, Scalar3, Vector2to2, Vector3to2};
+use crate::map_range;
+use crate::vec2::Vec2;
+use crate::vec3::Vec3;
+
+/// The sum of two fields: of their values for scalar fields, of their
+/// vectors for vector fields.
+#[derive(Clone)]
+pub struct Sum<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A, B> Sum<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Scalar2, B: Scalar2> Scalar2 for Sum<A, B> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.a.number2(pos) + self.b.number2(pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl<A: Scalar3, B: Scalar3> Scalar3 for Sum<A, B> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.a.number3(pos) + self.b.number3(pos)
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+impl<A: Vector2to2, B: Vector2to2> Vector2to2 for Sum<A, B> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        self.a.vec2(pos) + self.b.vec2(pos)
+    }
+}
+
+impl<A: Vector3to2, B: Vector3to2> Vector3to2 for Sum<A, B> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        self.a.vec3(pos) + self.b.vec3(pos)
+    }
+}
+
+/// The product of a scalar field `a` with another scalar field, or with a
+/// vector field scaling its vectors, `b`.
+#[derive(Clone)]
+pub struct Product<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A, B> Product<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Scalar2, B: Scalar2> Scalar2 for Product<A, B> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.a.number2(pos) * self.b.number2(pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl<A: Scalar3, B: Scalar3> Scalar3 for Product<A, B> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.a.number3(pos) * self.b.number3(pos)
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+impl<A: Scalar2, B: Vector2to2> Vector2to2 for Product<A, B> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        self.b.vec2(pos) * self.a.number2(pos)
+    }
+}
+
+impl<A: Scalar3, B: Vector3to2> Vector3to2 for Product<A, B> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        self.b.vec3(pos) * self.a.number3(pos)
+    }
+}
+
+/// A mix of two fields, going from `a` where `mask` is 0 to `b` where it is
+/// 1. The mask is clamped between 0 and 1.
+#[derive(Clone)]
+pub struct Blend<A, B, M> {
+    pub a: A,
+    pub b: B,
+    pub mask: M,
+}
+
+impl<A, B, M> Blend<A, B, M> {
+    pub fn new(a: A, b: B, mask: M) -> Self {
+        Self { a, b, mask }
+    }
+}
+
+impl<A: Scalar2, B: Scalar2, M: Scalar2> Scalar2 for Blend<A, B, M> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        let t = self.mask.number2(pos).clamp(0., 1.);
+        self.a.number2(pos) * (1. - t) + self.b.number2(pos) * t
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl<A: Scalar3, B: Scalar3, M: Scalar3> Scalar3 for Blend<A, B, M> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        let t = self.mask.number3(pos).clamp(0., 1.);
+        self.a.number3(pos) * (1. - t) + self.b.number3(pos) * t
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+impl<A: Vector2to2, B: Vector2to2, M: Scalar2> Vector2to2 for Blend<A, B, M> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        let t = self.mask.number2(pos).clamp(0., 1.);
+        self.a.vec2(pos) * (1. - t) + self.b.vec2(pos) * t
+    }
+}
+
+impl<A: Vector3to2, B: Vector3to2, M: Scalar3> Vector3to2 for Blend<A, B, M> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        let t = self.mask.number3(pos).clamp(0., 1.);
+        self.a.vec3(pos) * (1. - t) + self.b.vec3(pos) * t
+    }
+}
+
+/// A scalar field with its values mapped linearly from the range `from` to
+/// the range `to`.
+#[derive(Clone)]
+pub struct Remap<F> {
+    pub field: F,
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+}
+
+impl<F> Remap<F> {
+    pub fn new(field: F, from: (f64, f64), to: (f64, f64)) -> Self {
+        Self { field, from, to }
+    }
+
+    fn remap(&self, value: f64) -> f64 {
+        map_range(value, self.from.0, self.from.1, self.to.0, self.to.1)
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Remap<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.remap(self.field.number2(pos))
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl<F: Scalar3> Scalar3 for Remap<F> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.remap(self.field.number3(pos))
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+/// A scalar field with its values kept between `min` and `max`.
+#[derive(Clone)]
+pub struct Clamp<F> {
+    pub field: F,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl<F> Clamp<F> {
+    pub fn new(field: F, min: f64, max: f64) -> Self {
+        Self { field, min, max }
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Clamp<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        crate::clamp(self.field.number2(pos), self.min, self.max)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl<F: Scalar3> Scalar3 for Clamp<F> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        crate::clamp(self.field.number3(pos), self.min, self.max)
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+/// A field scaled, then rotated around the origin, then translated. The
+/// vectors of vector fields turn with it; 3D fields are transformed in the
+/// xy plane.
+#[derive(Clone)]
+pub struct Transform<F> {
+    pub field: F,
+    pub scale: f64,
+    pub rotation: Angle,
+    pub translation: Vec2,
+}
+
+impl<F> Transform<F> {
+    pub fn new(field: F) -> Self {
+        Self {
+            field,
+            scale: 1.,
+            rotation: Angle::zero(),
+            translation: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Angle) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_translation(mut self, translation: Vec2) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    /// Return where the untransformed field is sampled for `pos`.
+    fn source(&self, pos: Vec2) -> Vec2 {
+        (pos - self.translation).rotate(Angle::zero() - self.rotation) / self.scale
+    }
+
+    fn source3(&self, pos: Vec3) -> Vec3 {
+        let Vec2 { x, y } = self.source(Vec2::new(pos.x, pos.y));
+        Vec3::new(x, y, pos.z)
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Transform<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.field.number2(self.source(pos))
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        self.field.angle2(self.source(pos), increment)
+    }
+}
+
+impl<F: Scalar3> Scalar3 for Transform<F> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.field.number3(self.source3(pos))
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        self.field.angle3(self.source3(pos), increment)
+    }
+}
+
+impl<F: Vector2to2> Vector2to2 for Transform<F> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        self.field.vec2(self.source(pos)).rotate(self.rotation)
+    }
+}
+
+impl<F: Vector3to2> Vector3to2 for Transform<F> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        self.field.vec3(self.source3(pos)).rotate(self.rotation)
+    }
+}
+
+/// The gradient of a scalar field, pointing towards increasing values,
+/// estimated with central differences `epsilon` apart.
+#[derive(Clone)]
+pub struct Gradient<F> {
+    pub field: F,
+    pub epsilon: f64,
+}
+
+impl<F> Gradient<F> {
+    pub fn new(field: F, epsilon: f64) -> Self {
+        Self { field, epsilon }
+    }
+}
+
+impl<F: Scalar2> Vector2to2 for Gradient<F> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        partials2(&self.field, pos, self.epsilon)
+    }
+}
+
+impl<F: Scalar3> Vector3to2 for Gradient<F> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        partials3(&self.field, pos, self.epsilon)
+    }
+}
+
+/// The curl of a scalar field taken as a stream function: a divergence free
+/// flow going along its contours, estimated with central differences
+/// `epsilon` apart.
+#[derive(Clone)]
+pub struct Curl<F> {
+    pub field: F,
+    pub epsilon: f64,
+}
+
+impl<F> Curl<F> {
+    pub fn new(field: F, epsilon: f64) -> Self {
+        Self { field, epsilon }
+    }
+}
+
+impl<F: Scalar2> Vector2to2 for Curl<F> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        let d = partials2(&self.field, pos, self.epsilon);
+        Vec2::new(d.y, -d.x)
+    }
+}
+
+impl<F: Scalar3> Vector3to2 for Curl<F> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        let d = partials3(&self.field, pos, self.epsilon);
+        Vec2::new(d.y, -d.x)
+    }
+}
+
+/// Return the partial derivatives of `field` along x and y at `pos`.
+fn partials2<F: Scalar2>(field: &F, pos: Vec2, epsilon: f64) -> Vec2 {
+    let dx = Vec2::new(epsilon, 0.);
+    let dy = Vec2::new(0., epsilon);
+    Vec2::new(
+        field.number2(pos + dx) - field.number2(pos - dx),
+        field.number2(pos + dy) - field.number2(pos - dy),
+    ) / (2. * epsilon)
+}
+
+fn partials3<F: Scalar3>(field: &F, pos: Vec3, epsilon: f64) -> Vec2 {
+    let at = |x: f64, y: f64| field.number3(Vec3::new(pos.x + x, pos.y + y, pos.z));
+    Vec2::new(
+        at(epsilon, 0.) - at(-epsilon, 0.),
+        at(0., epsilon) - at(0., -epsilon),
+    ) / (2. * epsilon)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angle::Angle;
+    use crate::field::combinators::{Blend, Clamp, Curl, Gradient, Product, Remap, Sum, Transform};
+    use crate::field::{Scalar2, Scalar3, Vector2to2};
+    use crate::vec2::Vec2;
+    use crate::vec3::Vec3;
+    use approx::assert_relative_eq;
+
+    fn slope(pos: Vec2) -> f64 {
+        pos.x * 2. + pos.y
+    }
+
+    #[test]
+    fn closures_combine_as_fields() {
+        let p = Vec2::new(3., 4.);
+        assert_relative_eq!(Sum::new(slope, |_: Vec2| 1.).number2(p), 11.);
+        assert_relative_eq!(Product::new(slope, |_: Vec2| 0.5).number2(p), 5.);
+        let scaled = Product::new(slope, |_: Vec2| Vec2::new(1., -1.)).vec2(p);
+        assert_eq!(scaled, Vec2::new(10., -10.));
+        let mask = |pos: Vec2| pos.x / 10.;
+        assert_relative_eq!(Blend::new(slope, |_: Vec2| 0., mask).number2(p), 7.);
+        assert_relative_eq!(Remap::new(slope, (0., 20.), (-1., 1.)).number2(p), 0.);
+        assert_relative_eq!(Clamp::new(slope, 0., 5.).number2(p), 5.);
+        let depth = |pos: Vec3| pos.z;
+        assert_relative_eq!(Sum::new(depth, depth).number3(Vec3::new(0., 0., 2.)), 4.);
+    }
+
+    #[test]
+    fn transforms_move_the_domain() {
+        let p = Vec2::new(3., 4.);
+        let field = Transform::new(slope)
+            .with_scale(2.)
+            .with_rotation(Angle::from_degrees(90.))
+            .with_translation(Vec2::new(1., 1.));
+        // (3, 4) comes from (1.5, -1) scaled, turned a quarter and moved
+        assert_relative_eq!(field.number2(p), slope(Vec2::new(1.5, -1.)), epsilon = 1e-9);
+        let east = Transform::new(|_: Vec2| Vec2::new(1., 0.))
+            .with_rotation(Angle::from_degrees(90.))
+            .vec2(p);
+        assert_relative_eq!(east.x, 0., epsilon = 1e-9);
+        assert_relative_eq!(east.y, 1., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn gradient_and_curl_are_perpendicular() {
+        let bowl = |pos: Vec2| pos.x * pos.x + pos.y * pos.y;
+        let p = Vec2::new(3., 4.);
+        let gradient = Gradient::new(bowl, 1e-3).vec2(p);
+        assert_relative_eq!(gradient.x, 6., epsilon = 1e-6);
+        assert_relative_eq!(gradient.y, 8., epsilon = 1e-6);
+        // The flow circles the bowl, along its contours
+        let curl = Curl::new(bowl, 1e-3).vec2(p);
+        assert_relative_eq!(curl.x, 8., epsilon = 1e-6);
+        assert_relative_eq!(curl.y, -6., epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::angle::Angle;
+use crate::field::{value_to_angle, Scalar2, Scalar3, Vector2to2, Vector3to2};
+use crate::vec2::Vec2;
+use crate::vec3::Vec3;
+use noise::core::worley::ReturnType;
+use noise::{NoiseFn, OpenSimplex, Perlin, Simplex, Worley};
+
+/// The noise summed over the octaves of a `FractalField`.
+#[derive(Clone)]
+enum Source {
+    Perlin(Perlin),
+    Simplex(Simplex),
+    OpenSimplex(OpenSimplex),
+    Worley(Worley),
+}
+
+impl Source {
+    fn get2(&self, p: [f64; 2]) -> f64 {
+        match self {
+            Source::Perlin(n) => n.get(p),
+            Source::Simplex(n) => n.get(p),
+            Source::OpenSimplex(n) => n.get(p),
+            Source::Worley(n) => n.get(p),
+        }
+    }
+
+    fn get3(&self, p: [f64; 3]) -> f64 {
+        match self {
+            Source::Perlin(n) => n.get(p),
+            Source::Simplex(n) => n.get(p),
+            Source::OpenSimplex(n) => n.get(p),
+            Source::Worley(n) => n.get(p),
+        }
+    }
+}
+
+/// How the octaves of a `FractalField` are combined.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Combination {
+    /// Fractal Brownian motion: the octaves are added up
+    Fbm,
+    /// Ridged multifractal: the octaves are folded into sharp crests
+    Ridged,
+}
+
+/// Noise made of several octaves of increasing frequency and decreasing
+/// amplitude, with values between -1 and 1.
+#[derive(Clone)]
+pub struct FractalField {
+    /// The frequency of the first octave
+    pub frequency: f64,
+    /// The factor between the frequencies of consecutive octaves
+    pub lacunarity: f64,
+    /// The factor between the amplitudes of consecutive octaves
+    pub persistence: f64,
+    /// The displacement applied to positions, after scaling them by the
+    /// frequency
+    pub offset: Vec3,
+    seed: u32,
+    source: fn(u32) -> Source,
+    sources: Vec<Source>,
+    combination: Combination,
+}
+
+impl FractalField {
+    const DEFAULT_OCTAVES: usize = 6;
+
+    fn new(seed: u32, source: fn(u32) -> Source, combination: Combination) -> Self {
+        Self {
+            frequency: 1.,
+            lacunarity: 2.,
+            persistence: 0.5,
+            offset: Vec3::new(0., 0., 0.),
+            seed,
+            source,
+            sources: vec![],
+            combination,
+        }
+        .with_octaves(Self::DEFAULT_OCTAVES)
+    }
+
+    /// Fractal Brownian motion over Perlin noise
+    pub fn fbm(seed: u32) -> Self {
+        Self::new(seed, |s| Source::Perlin(Perlin::new(s)), Combination::Fbm)
+    }
+
+    /// Ridged multifractal over Perlin noise, making sharp crests
+    pub fn ridged(seed: u32) -> Self {
+        Self::new(
+            seed,
+            |s| Source::Perlin(Perlin::new(s)),
+            Combination::Ridged,
+        )
+    }
+
+    /// Cellular noise, growing with the distance to the nearest of random
+    /// points
+    pub fn worley(seed: u32) -> Self {
+        Self::new(
+            seed,
+            |s| Source::Worley(Worley::new(s).set_return_type(ReturnType::Distance)),
+            Combination::Fbm,
+        )
+    }
+
+    /// Fractal Brownian motion over simplex noise
+    pub fn simplex(seed: u32) -> Self {
+        Self::new(seed, |s| Source::Simplex(Simplex::new(s)), Combination::Fbm)
+    }
+
+    /// Fractal Brownian motion over OpenSimplex noise
+    pub fn open_simplex(seed: u32) -> Self {
+        Self::new(
+            seed,
+            |s| Source::OpenSimplex(OpenSimplex::new(s)),
+            Combination::Fbm,
+        )
+    }
+
+    /// Return the number of octaves.
+    pub fn octaves(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Set the number of octaves, at least one, each one seeded differently.
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        self.sources = (0..octaves.max(1) as u32)
+            .map(|octave| (self.source)(self.seed.wrapping_add(octave)))
+            .collect();
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    pub fn with_persistence(mut self, persistence: f64) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return the weighted average of the octaves sampled by `sample`, given
+    /// each source and the frequency of its octave.
+    fn combine(&self, sample: impl Fn(&Source, f64) -> f64) -> f64 {
+        let (mut total, mut weight) = (0., 0.);
+        let (mut frequency, mut amplitude) = (self.frequency, 1.);
+        for source in &self.sources {
+            let value = sample(source, frequency);
+            total += amplitude
+                * match self.combination {
+                    Combination::Fbm => value,
+                    Combination::Ridged => (1. - value.abs()).powi(2) * 2. - 1.,
+                };
+            weight += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+        total / weight
+    }
+
+    /// Return the value at `pos`, before it is clamped between -1 and 1.
+    fn sample2(&self, pos: Vec2) -> f64 {
+        self.combine(|source, frequency| {
+            source.get2([
+                pos.x * frequency + self.offset.x,
+                pos.y * frequency + self.offset.y,
+            ])
+        })
+    }
+
+    /// Return the value at `pos`, before it is clamped between -1 and 1.
+    fn sample3(&self, pos: Vec3) -> f64 {
+        self.combine(|source, frequency| {
+            source.get3([
+                pos.x * frequency + self.offset.x,
+                pos.y * frequency + self.offset.y,
+                pos.z * frequency + self.offset.z,
+            ])
+        })
+    }
+}
+
+impl Scalar2 for FractalField {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.sample2(pos).clamp(-1., 1.)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl Scalar3 for FractalField {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.sample3(pos).clamp(-1., 1.)
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        value_to_angle(self.number3(pos), increment)
+    }
+}
+
+impl Vector2to2 for FractalField {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        Vec2::from_polar(self.angle2(pos, 0.), 1.)
+    }
+}
+
+impl Vector3to2 for FractalField {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        Vec2::from_polar(self.angle3(pos, 0.), 1.)
+    }
+}
+
+/// A field sampled at positions displaced by another one, `warp`, in the
+/// style of Inigo Quilez: each coordinate is moved by `strength` times the
+/// value of `warp` at a different spot.
+#[derive(Clone)]
+pub struct DomainWarp<F, W> {
+    /// The field being warped
+    pub field: F,
+    /// The field displacing the positions
+    pub warp: W,
+    /// The largest displacement
+    pub strength: f64,
+}
+
+// Where the warp is sampled for each coordinate, far apart so that the
+// displacements are unrelated at any sensible frequency
+const WARP_OFFSETS: [Vec3; 3] = [
+    Vec3 {
+        x: 0.,
+        y: 0.,
+        z: 0.,
+    },
+    Vec3 {
+        x: 5203.1,
+        y: 1307.9,
+        z: 2801.3,
+    },
+    Vec3 {
+        x: 1709.7,
+        y: 9211.3,
+        z: 4127.1,
+    },
+];
+
+impl<F, W> DomainWarp<F, W> {
+    pub fn new(field: F, warp: W, strength: f64) -> Self {
+        Self {
+            field,
+            warp,
+            strength,
+        }
+    }
+}
+
+impl<F, W: Scalar2> DomainWarp<F, W> {
+    fn warp2(&self, pos: Vec2) -> Vec2 {
+        let [dx, dy, _] =
+            WARP_OFFSETS.map(|o| self.warp.number2(pos + Vec2::new(o.x, o.y)) * self.strength);
+        pos + Vec2::new(dx, dy)
+    }
+}
+
+impl<F, W: Scalar3> DomainWarp<F, W> {
+    fn warp3(&self, pos: Vec3) -> Vec3 {
+        let [dx, dy, dz] = WARP_OFFSETS.map(|o| self.warp.number3(pos + o) * self.strength);
+        pos + Vec3::new(dx, dy, dz)
+    }
+}
+
+impl<F: Scalar2, W: Scalar2> Scalar2 for DomainWarp<F, W> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.field.number2(self.warp2(pos))
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        self.field.angle2(self.warp2(pos), increment)
+    }
+}
+
+impl<F: Scalar3, W: Scalar3> Scalar3 for DomainWarp<F, W> {
+    fn number3(&self, pos: Vec3) -> f64 {
+        self.field.number3(self.warp3(pos))
+    }
+
+    fn angle3(&self, pos: Vec3, increment: f64) -> Angle {
+        self.field.angle3(self.warp3(pos), increment)
+    }
+}
+
+impl<F: Vector2to2, W: Scalar2> Vector2to2 for DomainWarp<F, W> {
+    fn vec2(&self, pos: Vec2) -> Vec2 {
+        self.field.vec2(self.warp2(pos))
+    }
+}
+
+impl<F: Vector3to2, W: Scalar3> Vector3to2 for DomainWarp<F, W> {
+    fn vec3(&self, pos: Vec3) -> Vec2 {
+        self.field.vec3(self.warp3(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field::fractal::{DomainWarp, FractalField};
+    use crate::field::Scalar2;
+    use crate::vec2::Vec2;
+    use crate::vec3::Vec3;
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(FractalField::fbm(7))]
+    #[case(FractalField::ridged(7))]
+    #[case(FractalField::worley(7))]
+    #[case(FractalField::simplex(7))]
+    #[case(FractalField::open_simplex(7))]
+    fn fractal_fields_stay_in_range(#[case] field: FractalField) {
+        let field = field.with_frequency(0.1).with_octaves(4);
+        assert_eq!(field.octaves(), 4);
+        let values = (0..400)
+            .map(|i| Vec2::new((i % 20) as f64 * 1.7, (i / 20) as f64 * 1.3))
+            .map(|p| field.sample2(p))
+            .collect::<Vec<f64>>();
+        let (min, max) = values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(a, b), v| (a.min(*v), b.max(*v)));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!(values.iter().all(|v| (-1. ..=1.).contains(v)));
+        // The field is not flat, and spreads around zero
+        assert!(max - min > 0.3);
+        assert!(mean.abs() < 0.2);
+        let p = Vec3::new(3.3, 4.4, 0.);
+        assert!((-1. ..=1.).contains(&field.sample3(p)));
+    }
+
+    #[test]
+    fn frequency_and_offset_move_the_samples() {
+        let base = FractalField::fbm(3).with_octaves(1);
+        let scaled = base.clone().with_frequency(2.);
+        let p = Vec2::new(1.3, 2.9);
+        assert_relative_eq!(scaled.number2(p), base.number2(p * 2.));
+        let moved = base.clone().with_offset(Vec3::new(0.5, 0.25, 0.));
+        assert_relative_eq!(moved.number2(p), base.number2(p + Vec2::new(0.5, 0.25)));
+    }
+
+    #[test]
+    fn domain_warp_displaces_the_field() {
+        let field = FractalField::simplex(1).with_frequency(0.05);
+        let warp = FractalField::fbm(2).with_frequency(0.02);
+        let still = DomainWarp::new(field.clone(), warp.clone(), 0.);
+        let warped = DomainWarp::new(field.clone(), warp, 20.);
+        let p = Vec2::new(12., 34.);
+        assert_relative_eq!(still.number2(p), field.number2(p));
+        assert!((warped.number2(p) - field.number2(p)).abs() > 1e-6);
+    }
+}
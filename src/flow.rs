@@ -0,0 +1,318 @@
+//! Streamlines traced through vector fields
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::field::Vector2to2;
+use crate::shapes::linestring::LineString;
+use crate::traits::ToGeoPolygon;
+use crate::vec2::Vec2;
+use geo::{coord, Contains};
+
+// Below this speed, the field is considered to vanish
+const STALL: f64 = 1e-9;
+
+/// The settings of streamlines following a vector field, integrated with
+/// the fourth-order Runge-Kutta method. Each line goes both ways from its
+/// seed, for up to half of `max_length` each way, until it leaves the
+/// boundary, the field vanishes, it comes back onto itself or, when a
+/// `separation` is set, it comes closer than half of it to another line or
+/// to its own earlier points.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Streamlines {
+    /// The distance between consecutive points of a line
+    pub step: f64,
+    /// The longest a line can grow
+    pub max_length: f64,
+    /// The distance kept between lines, seeding new ones alongside the
+    /// existing ones when set
+    pub separation: Option<f64>,
+}
+
+impl Streamlines {
+    pub fn new(step: f64, max_length: f64) -> Self {
+        Self {
+            step,
+            max_length,
+            separation: None,
+        }
+    }
+
+    /// Keep lines `separation` apart, in the style of Jobard and Lefer.
+    pub fn with_separation(mut self, separation: f64) -> Self {
+        self.separation = Some(separation);
+        self
+    }
+
+    /// Trace the lines following `field` from `seeds`, inside `boundary`.
+    /// Seeds closer than `separation` to an existing line are skipped. There
+    /// are no lines when the step or the separation is not positive.
+    pub fn trace<F: Vector2to2, T: ToGeoPolygon>(
+        &self,
+        field: &F,
+        seeds: &[Vec2],
+        boundary: &T,
+    ) -> Vec<LineString> {
+        if self.step <= 0. || self.separation.is_some_and(|s| s <= 0.) {
+            return vec![];
+        }
+        let boundary = boundary.to_geo_polygon();
+        let inside = |p: Vec2| boundary.contains(&coord! { x: p.x, y: p.y });
+        let mut grid = self.separation.map(Grid::new);
+        let mut lines = vec![];
+        let mut queue = seeds.to_vec();
+        queue.reverse();
+        while let Some(seed) = queue.pop() {
+            if !inside(seed) || grid.as_ref().is_some_and(|g| g.near(seed, g.cell)) {
+                continue;
+            }
+            let mut trail = self.trail();
+            trail.push(seed);
+            let mut backward = self.follow(field, seed, -1., &inside, grid.as_ref(), &mut trail);
+            // The forward half keeps away from the backward one, whose end
+            // at the seed is the most recent
+            let mut trail = self.trail();
+            backward.iter().rev().for_each(|p| trail.push(*p));
+            trail.push(seed);
+            let forward = self.follow(field, seed, 1., &inside, grid.as_ref(), &mut trail);
+            backward.reverse();
+            backward.push(seed);
+            backward.extend(forward);
+            if backward.len() < 2 {
+                continue;
+            }
+            if let Some(grid) = grid.as_mut() {
+                grid.insert(&backward);
+                // Seed new lines on both sides of this one
+                let offset = grid.cell;
+                for pair in backward.windows(2) {
+                    let (a, b) = (pair[0], pair[1]);
+                    let normal = Vec2::new(a.y - b.y, b.x - a.x) / a.distance(b);
+                    queue.push(a - normal * offset);
+                    queue.push(a + normal * offset);
+                }
+            }
+            lines.push(LineString::new(backward));
+        }
+        lines
+    }
+
+    /// Return an empty trail for a line, which stops within half of the
+    /// separation of its own points, or within a step without one, leaving
+    /// out those less than twice that distance behind along the line.
+    fn trail(&self) -> Trail {
+        let reach = self.separation.map_or(self.step, |s| s / 2.);
+        let window = (2. * reach / self.step).ceil() as usize + 1;
+        Trail {
+            older: Grid::new(reach),
+            recent: VecDeque::new(),
+            window,
+        }
+    }
+
+    /// Return the points reached from `seed` going along the field, or
+    /// against it when `sign` is negative, excluding the seed. The points
+    /// are added to `trail`.
+    fn follow<F: Vector2to2>(
+        &self,
+        field: &F,
+        seed: Vec2,
+        sign: f64,
+        inside: &impl Fn(Vec2) -> bool,
+        grid: Option<&Grid>,
+        trail: &mut Trail,
+    ) -> Vec<Vec2> {
+        let mut points = vec![];
+        let mut position = seed;
+        let mut length = 0.;
+        while length + self.step <= self.max_length / 2. {
+            let Some(next) = rk4(field, position, self.step * sign) else {
+                break;
+            };
+            if !inside(next) || grid.is_some_and(|g| g.near(next, g.cell / 2.)) {
+                break;
+            }
+            // Stop before looping back onto the line itself
+            if trail.near(next) {
+                break;
+            }
+            trail.push(next);
+            points.push(next);
+            position = next;
+            length += self.step;
+        }
+        points
+    }
+}
+
+/// Move from `p` by `step` along the direction of `field`, with the
+/// fourth-order Runge-Kutta method, or return `None` where the field vanishes.
+fn rk4<F: Vector2to2>(field: &F, p: Vec2, step: f64) -> Option<Vec2> {
+    let direction = |q: Vec2| {
+        let v = field.vec2(q);
+        let speed = v.distance(Vec2::ZERO);
+        (speed > STALL).then(|| v / speed)
+    };
+    let k1 = direction(p)?;
+    let k2 = direction(p + k1 * (step / 2.))?;
+    let k3 = direction(p + k2 * (step / 2.))?;
+    let k4 = direction(p + k3 * step)?;
+    Some(p + (k1 + k2 * 2. + k3 * 2. + k4) * (step / 6.))
+}
+
+/// The points of the line being traced: the older ones, which it must keep
+/// away from, and the last `window` ones, too close along the line to count.
+struct Trail {
+    older: Grid,
+    recent: VecDeque<Vec2>,
+    window: usize,
+}
+
+impl Trail {
+    fn push(&mut self, p: Vec2) {
+        self.recent.push_back(p);
+        if self.recent.len() > self.window {
+            let old = self.recent.pop_front().unwrap();
+            self.older.insert(&[old]);
+        }
+    }
+
+    fn near(&self, p: Vec2) -> bool {
+        self.older.near(p, self.older.cell)
+    }
+}
+
+/// The points of the lines traced so far, bucketed in square cells.
+struct Grid {
+    cell: f64,
+    buckets: HashMap<(i64, i64), Vec<Vec2>>,
+}
+
+impl Grid {
+    fn new(cell: f64) -> Self {
+        Self {
+            cell,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn key(&self, p: Vec2) -> (i64, i64) {
+        (
+            (p.x / self.cell).floor() as i64,
+            (p.y / self.cell).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, points: &[Vec2]) {
+        for p in points {
+            let key = self.key(*p);
+            self.buckets.entry(key).or_default().push(*p);
+        }
+    }
+
+    /// Return whether a point lies closer than `distance`, at most one
+    /// cell, to `p`.
+    fn near(&self, p: Vec2, distance: f64) -> bool {
+        let (x, y) = self.key(p);
+        (x - 1..=x + 1)
+            .flat_map(|i| (y - 1..=y + 1).map(move |j| (i, j)))
+            .filter_map(|key| self.buckets.get(&key))
+            .flatten()
+            .any(|q| q.distance(p) < distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field::Vector2to2;
+    use crate::flow::Streamlines;
+    use crate::prelude::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::TAU;
+
+    // Circles around the origin
+    struct Vortex;
+
+    impl Vector2to2 for Vortex {
+        fn vec2(&self, pos: Vec2) -> Vec2 {
+            Vec2::new(-pos.y, pos.x)
+        }
+    }
+
+    // A uniform flow to the right
+    struct Wind;
+
+    impl Vector2to2 for Wind {
+        fn vec2(&self, _pos: Vec2) -> Vec2 {
+            Vec2::new(1., 0.)
+        }
+    }
+
+    #[test]
+    fn streamlines_follow_the_field() {
+        let boundary = Circle::new(Vec2::ZERO, 100.);
+        let lines = Streamlines::new(0.5, 50.).trace(&Vortex, &[Vec2::new(20., 0.)], &boundary);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].points.len(), 101);
+        assert!(lines[0]
+            .points
+            .iter()
+            .all(|p| (p.distance(Vec2::ZERO) - 20.).abs() < 1e-6));
+    }
+
+    #[test]
+    fn streamlines_stop_when_they_loop() {
+        let boundary = Circle::new(Vec2::ZERO, 100.);
+        let seed = Vec2::new(20., 0.);
+        let plain = Streamlines::new(0.5, 1000.).trace(&Vortex, &[seed], &boundary);
+        let separated =
+            Streamlines::new(0.5, 1000.)
+                .with_separation(4.)
+                .trace(&Vortex, &[seed], &boundary);
+        for lines in [plain, separated] {
+            // Going round once, less the gap closing the loop
+            let points = &lines[0].points;
+            let length = (points.len() - 1) as f64 * 0.5;
+            assert!(length < TAU * 20.);
+            assert!(length > TAU * 20. - 4.);
+            assert!(points[0].distance(points[points.len() - 1]) < 4.);
+        }
+    }
+
+    #[test]
+    fn streamlines_stop_at_the_boundary() {
+        let boundary = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let lines = Streamlines::new(1., 100.).trace(&Wind, &[Vec2::new(4.5, 5.)], &boundary);
+        assert_eq!(lines.len(), 1);
+        let points = &lines[0].points;
+        assert_relative_eq!(points[0].x, 0.5);
+        assert_relative_eq!(points[points.len() - 1].x, 9.5);
+    }
+
+    #[test]
+    fn separations_must_be_positive() {
+        let boundary = Rect::new(Vec2::new(0., 0.), 20., 20.);
+        for separation in [0., -1.] {
+            let lines = Streamlines::new(1., 100.)
+                .with_separation(separation)
+                .trace(&Wind, &[Vec2::new(10., 10.)], &boundary);
+            assert!(lines.is_empty());
+        }
+    }
+
+    #[test]
+    fn separated_streamlines_fill_the_boundary() {
+        let boundary = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let lines = Streamlines::new(1., 1000.).with_separation(10.).trace(
+            &Wind,
+            &[Vec2::new(50.5, 55.)],
+            &boundary,
+        );
+        let mut heights = lines.iter().map(|l| l.points[0].y).collect::<Vec<f64>>();
+        heights.sort_by(f64::total_cmp);
+        assert_eq!(heights.len(), 10);
+        for pair in heights.windows(2) {
+            assert_relative_eq!(pair[1] - pair[0], 10., epsilon = 1e-9);
+        }
+        assert!(lines.iter().all(|l| l.points.len() == 100));
+    }
+}
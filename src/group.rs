@@ -1,8 +1,14 @@
+use crate::occlusion::occlude_shapes;
+use crate::optimize::dedup::remove_duplicate_segments;
+use crate::optimize::{
+    merge_lines, optimize_path_order, pen_lifts, pen_up_distance, MergeReport, PathOrderReport,
+};
 use crate::prelude::Pen;
 use crate::shapes::linestring::LineString;
 use crate::style::Style;
 use crate::traits::ToShape;
 use crate::Shape;
+use std::time::Instant;
 
 #[derive(Clone)]
 pub struct Group {
@@ -29,7 +35,10 @@ impl Group {
     pub fn add<T: ToShape>(&mut self, element: T) {
         let shape = element.to_shape();
         match shape {
+            Shape::Arc(s) => self.elements.push(Shape::Arc(s)),
+            Shape::Bezier(s) => self.elements.push(Shape::Bezier(s)),
             Shape::Circle(s) => self.elements.push(Shape::Circle(s)),
+            Shape::Ellipse(s) => self.elements.push(Shape::Ellipse(s)),
             Shape::Rectangle(s) => self.elements.push(Shape::Rectangle(s)),
             Shape::Hexagon(s) => self.elements.push(Shape::Hexagon(s)),
             Shape::LineString(s) => self.elements.push(Shape::LineString(s)),
@@ -43,7 +52,10 @@ impl Group {
         for element in elements {
             let shape = element.to_shape();
             match shape {
+                Shape::Arc(s) => self.elements.push(Shape::Arc(s)),
+                Shape::Bezier(s) => self.elements.push(Shape::Bezier(s)),
                 Shape::Circle(s) => self.elements.push(Shape::Circle(s)),
+                Shape::Ellipse(s) => self.elements.push(Shape::Ellipse(s)),
                 Shape::Rectangle(s) => self.elements.push(Shape::Rectangle(s)),
                 Shape::Hexagon(s) => self.elements.push(Shape::Hexagon(s)),
                 Shape::LineString(s) => self.elements.push(Shape::LineString(s)),
@@ -54,6 +66,45 @@ impl Group {
         }
     }
 
+    /// Reorder the elements to reduce the distance travelled with the pen up.
+    pub fn optimize_path_order(&mut self) -> PathOrderReport {
+        let start = Instant::now();
+        let travel_before = pen_up_distance(&self.elements);
+        self.elements = optimize_path_order(std::mem::take(&mut self.elements));
+        PathOrderReport {
+            travel_before,
+            travel_after: pen_up_distance(&self.elements),
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Join `LineString`s whose endpoints are closer than `tolerance`, closing
+    /// loops into `Polygon`s, to reduce the number of pen lifts.
+    pub fn merge_lines(&mut self, tolerance: f64) -> MergeReport {
+        let pen_lifts_before = pen_lifts(&self.elements);
+        self.elements = merge_lines(std::mem::take(&mut self.elements), tolerance);
+        MergeReport {
+            pen_lifts_before,
+            pen_lifts_after: pen_lifts(&self.elements),
+        }
+    }
+
+    /// Remove the segments drawn more than once, within `tolerance`, and
+    /// re-chain the remaining ones.
+    pub fn remove_duplicate_segments(&mut self, tolerance: f64) {
+        let elements = std::mem::take(&mut self.elements);
+        self.elements = remove_duplicate_segments(vec![elements], tolerance)
+            .pop()
+            .unwrap();
+    }
+
+    /// Remove the parts of the elements, taken front to back, hidden behind
+    /// the closed shapes before them. Hidden shapes become polylines
+    /// approximating their visible parts within `tolerance`.
+    pub fn occlude(&mut self, tolerance: f64) {
+        self.elements = occlude_shapes(std::mem::take(&mut self.elements), tolerance);
+    }
+
     pub fn linestrings(&self) -> Vec<LineString> {
         let mut lstrs = vec![];
         self.elements.iter().for_each(|e| {
@@ -0,0 +1,881 @@
+//! Read SVG files produced by other tools into groups of shapes
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::angle::Angle;
+use crate::group::Group;
+use crate::shapes::arc::segments_for_tolerance;
+use crate::shapes::bezier::Curve;
+use crate::shapes::circle::Circle;
+use crate::shapes::ellipse::Ellipse;
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::style::Style;
+use crate::uom::Uom;
+use crate::vec2::Vec2;
+use crate::Shape;
+use anyhow::{anyhow, Context, Result};
+use svg::node::element::path::{Command, Data, Position};
+use svg::node::element::tag::Type;
+use svg::node::Attributes;
+use svg::parser::Event;
+
+// Elements whose content is never drawn directly
+const NOT_RENDERED: [&str; 9] = [
+    "clipPath", "defs", "marker", "mask", "metadata", "pattern", "style", "symbol", "title",
+];
+
+/// Read the SVG file at `path`, see `parse_svg`.
+pub fn import_svg(path: &str, uom: Uom, tolerance: f64) -> Result<Vec<Group>> {
+    let content = std::fs::read_to_string(path).context("Cannot open SVG file")?;
+    parse_svg(&content, uom, tolerance)
+}
+
+/// Convert the SVG document `content` into groups of shapes expressed in `uom`.
+///
+/// Every Inkscape layer becomes a group, styled with the stroke of the layer,
+/// or of its first stroked element. Elements outside any layer are collected
+/// in a first group, dropped if empty. Transforms and the `viewBox` are
+/// applied, and curves are flattened to lines deviating at most `tolerance`
+/// from the original. Closed paths become polygons, with the subpaths they
+/// surround as holes, circles and ellipses are
+/// kept as such as long as they are not distorted by a transform.
+pub fn parse_svg(content: &str, uom: Uom, tolerance: f64) -> Result<Vec<Group>> {
+    let tolerance = Uom::convert_scalar(tolerance, uom, Uom::Px);
+    let mut groups = vec![Group::new()];
+    let mut styled = vec![false];
+    let mut stack = vec![State::default()];
+
+    for event in svg::read(content).context("Cannot parse SVG")? {
+        let (name, kind, attributes) = match event {
+            Event::Tag(name, kind, attributes) => (name, kind, attributes),
+            Event::Error(e) => return Err(e).context("Cannot parse SVG"),
+            _ => continue,
+        };
+        if kind == Type::End {
+            if stack.len() > 1 {
+                stack.pop();
+            }
+            continue;
+        }
+
+        let mut state = stack.last().cloned().unwrap_or_default();
+        let display = property(&attributes, "display");
+        if NOT_RENDERED.contains(&name) || display.as_deref() == Some("none") {
+            state.hidden = true;
+        }
+        if !state.hidden {
+            if name == "svg" && stack.len() == 1 {
+                state.transform = viewport(&attributes)?;
+            }
+            if let Some(transform) = attributes.get("transform") {
+                let local = Transform::parse(transform)
+                    .ok_or_else(|| anyhow!("Invalid transform '{}'", transform.to_string()))?;
+                state.transform = state.transform.compose(&local);
+            }
+            if let Some(stroke) = property(&attributes, "stroke") {
+                state.stroke = Some(stroke);
+            }
+            if let Some(width) = property(&attributes, "stroke-width") {
+                state.stroke_width =
+                    Uom::parse_css_length(&width).map(|w| w * state.transform.scale());
+            }
+
+            let layer = name == "g"
+                && attributes
+                    .get("inkscape:groupmode")
+                    .is_some_and(|mode| &**mode == "layer");
+            if layer {
+                groups.push(Group::new());
+                styled.push(false);
+                state.group = groups.len() - 1;
+            }
+            let mut builder = PathBuilder::new(state.transform, tolerance);
+            let shapes = trace(name, &attributes, &mut builder)?;
+            let drawn = !shapes.is_empty();
+            let group = &mut groups[state.group];
+            group
+                .elements
+                .extend(shapes.into_iter().map(|s| s.to_uom(uom)));
+            if (layer || drawn) && !styled[state.group] {
+                if let Some(stroke) = state.stroke.as_ref().filter(|s| *s != "none") {
+                    let width = state.stroke_width.unwrap_or(state.transform.scale());
+                    group.set_style(Style::new(stroke, &format!("{}px", round(width))));
+                    styled[state.group] = true;
+                }
+            }
+        }
+        if kind == Type::Start {
+            stack.push(state);
+        }
+    }
+
+    if groups[0].elements.is_empty() {
+        groups.remove(0);
+    }
+    Ok(groups)
+}
+
+/// Properties inherited by the children of an element.
+#[derive(Clone)]
+struct State {
+    transform: Transform,
+    stroke: Option<String>,
+    /// Stroke width in pixels
+    stroke_width: Option<f64>,
+    group: usize,
+    hidden: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            stroke: None,
+            stroke_width: None,
+            group: 0,
+            hidden: false,
+        }
+    }
+}
+
+/// A shape in pixels, before conversion to the requested unit of measure.
+enum Traced {
+    Circle(Vec2, f64),
+    Ellipse(Vec2, f64, f64, Angle),
+    Open(Vec<Vec2>),
+    Closed(Vec<Vec2>, Vec<Vec<Vec2>>),
+}
+
+impl Traced {
+    fn to_uom(&self, uom: Uom) -> Shape {
+        let convert = |points: &[Vec2]| {
+            points
+                .iter()
+                .map(|p| Uom::convert_vec2(*p, Uom::Px, uom))
+                .collect::<Vec<Vec2>>()
+        };
+        match self {
+            Traced::Circle(center, radius) => Shape::Circle(Circle::new(
+                Uom::convert_vec2(*center, Uom::Px, uom),
+                Uom::convert_scalar(*radius, Uom::Px, uom),
+            )),
+            Traced::Ellipse(center, rx, ry, rotation) => Shape::Ellipse(Ellipse::new(
+                Uom::convert_vec2(*center, Uom::Px, uom),
+                Uom::convert_scalar(*rx, Uom::Px, uom),
+                Uom::convert_scalar(*ry, Uom::Px, uom),
+                *rotation,
+            )),
+            Traced::Open(points) => Shape::LineString(LineString::new(convert(points))),
+            Traced::Closed(points, interiors) => Shape::Polygon(Polygon::with_interiors(
+                convert(points),
+                interiors.iter().map(|ring| convert(ring)).collect(),
+            )),
+        }
+    }
+}
+
+/// A 2D affine transformation mapping `(x, y)` to
+/// `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Transform {
+    const IDENTITY: Self = Self::new(1., 0., 0., 1., 0., 0.);
+
+    const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    fn translate(x: f64, y: f64) -> Self {
+        Self::new(1., 0., 0., 1., x, y)
+    }
+
+    fn scale_xy(x: f64, y: f64) -> Self {
+        Self::new(x, 0., 0., y, 0., 0.)
+    }
+
+    /// Return the transformation applying `other` first, then `self`.
+    fn compose(&self, other: &Self) -> Self {
+        Self::new(
+            self.a * other.a + self.c * other.b,
+            self.b * other.a + self.d * other.b,
+            self.a * other.c + self.c * other.d,
+            self.b * other.c + self.d * other.d,
+            self.a * other.e + self.c * other.f + self.e,
+            self.b * other.e + self.d * other.f + self.f,
+        )
+    }
+
+    fn apply(&self, p: Vec2) -> Vec2 {
+        Vec2::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    /// Average scale factor, used to convert lengths.
+    fn scale(&self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// Whether circles are still circles once transformed.
+    fn is_similarity(&self) -> bool {
+        let (x, y) = (self.a.hypot(self.b), self.c.hypot(self.d));
+        (x - y).abs() <= 1e-9 * x.max(y)
+            && (self.a * self.c + self.b * self.d).abs() <= 1e-9 * x * y
+    }
+
+    /// Parse the value of a `transform` attribute.
+    fn parse(value: &str) -> Option<Self> {
+        let mut transform = Self::IDENTITY;
+        let mut rest = value.trim();
+        while !rest.is_empty() {
+            let (name, args) = rest.split_once('(')?;
+            let (args, tail) = args.split_once(')')?;
+            let args = parse_numbers(args)?;
+            let local = match (name.trim(), args.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Self::new(a, b, c, d, e, f),
+                ("translate", &[x]) => Self::translate(x, 0.),
+                ("translate", &[x, y]) => Self::translate(x, y),
+                ("scale", &[s]) => Self::scale_xy(s, s),
+                ("scale", &[x, y]) => Self::scale_xy(x, y),
+                ("rotate", &[angle]) => Self::rotate(angle),
+                ("rotate", &[angle, x, y]) => Self::translate(x, y)
+                    .compose(&Self::rotate(angle))
+                    .compose(&Self::translate(-x, -y)),
+                ("skewX", &[angle]) => Self::new(1., 0., angle.to_radians().tan(), 1., 0., 0.),
+                ("skewY", &[angle]) => Self::new(1., angle.to_radians().tan(), 0., 1., 0., 0.),
+                _ => return None,
+            };
+            transform = transform.compose(&local);
+            rest = tail.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        }
+        Some(transform)
+    }
+
+    fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self::new(cos, sin, -sin, cos, 0., 0.)
+    }
+}
+
+/// Return the transformation from the user space of the root `svg` element to pixels.
+fn viewport(attributes: &Attributes) -> Result<Transform> {
+    let Some(view_box) = attributes.get("viewBox") else {
+        return Ok(Transform::IDENTITY);
+    };
+    let [x, y, w, h] = parse_numbers(view_box)
+        .as_deref()
+        .and_then(|n| n.try_into().ok())
+        .filter(|&[_, _, w, h]: &[f64; 4]| w > 0. && h > 0.)
+        .ok_or_else(|| anyhow!("Invalid viewBox '{}'", view_box.to_string()))?;
+    let width = length(attributes, "width").unwrap_or(w);
+    let height = length(attributes, "height").unwrap_or(h);
+    let (sx, sy) = (width / w, height / h);
+    let aspect = attributes.get("preserveAspectRatio").map(|a| a.to_string());
+    if aspect.as_deref().is_some_and(|a| a.starts_with("none")) {
+        return Ok(Transform::scale_xy(sx, sy).compose(&Transform::translate(-x, -y)));
+    }
+    // Default behaviour: uniform scaling, centered in the viewport
+    let s = sx.min(sy);
+    Ok(
+        Transform::translate((width - w * s) / 2., (height - h * s) / 2.)
+            .compose(&Transform::scale_xy(s, s))
+            .compose(&Transform::translate(-x, -y)),
+    )
+}
+
+/// Return the shapes drawn by the element `name`, in pixels.
+fn trace(name: &str, attributes: &Attributes, builder: &mut PathBuilder) -> Result<Vec<Traced>> {
+    let len = |name: &str| length(attributes, name).unwrap_or(0.);
+    match name {
+        "path" => {
+            if let Some(d) = attributes.get("d") {
+                let data = Data::parse(d).context("Invalid path data")?;
+                builder.trace_path(&data);
+            }
+        }
+        "line" => {
+            builder.move_to(Vec2::new(len("x1"), len("y1")));
+            builder.line_to(Vec2::new(len("x2"), len("y2")));
+        }
+        "polyline" | "polygon" => {
+            let points = attributes
+                .get("points")
+                .map(|p| parse_numbers(p).unwrap_or_default())
+                .unwrap_or_default();
+            for (i, p) in points.chunks_exact(2).enumerate() {
+                let p = Vec2::new(p[0], p[1]);
+                if i == 0 {
+                    builder.move_to(p);
+                } else {
+                    builder.line_to(p);
+                }
+            }
+            if name == "polygon" {
+                builder.close();
+            }
+        }
+        "rect" => {
+            let (x, y, w, h) = (len("x"), len("y"), len("width"), len("height"));
+            if w > 0. && h > 0. {
+                let (rx, ry) = match (length(attributes, "rx"), length(attributes, "ry")) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(r), None) | (None, Some(r)) => (r, r),
+                    (None, None) => (0., 0.),
+                };
+                let (rx, ry) = (rx.clamp(0., w / 2.), ry.clamp(0., h / 2.));
+                builder.move_to(Vec2::new(x + rx, y));
+                builder.line_to(Vec2::new(x + w - rx, y));
+                builder.arc_to(rx, ry, 0., false, true, Vec2::new(x + w, y + ry));
+                builder.line_to(Vec2::new(x + w, y + h - ry));
+                builder.arc_to(rx, ry, 0., false, true, Vec2::new(x + w - rx, y + h));
+                builder.line_to(Vec2::new(x + rx, y + h));
+                builder.arc_to(rx, ry, 0., false, true, Vec2::new(x, y + h - ry));
+                builder.line_to(Vec2::new(x, y + ry));
+                builder.arc_to(rx, ry, 0., false, true, Vec2::new(x + rx, y));
+                builder.close();
+            }
+        }
+        "circle" => {
+            let (center, r) = (Vec2::new(len("cx"), len("cy")), len("r"));
+            if r > 0. && builder.transform.is_similarity() {
+                return Ok(vec![Traced::Circle(
+                    builder.transform.apply(center),
+                    r * builder.transform.scale(),
+                )]);
+            }
+            builder.ellipse(center, r, r);
+        }
+        "ellipse" => {
+            let (center, rx, ry) = (Vec2::new(len("cx"), len("cy")), len("rx"), len("ry"));
+            let t = builder.transform;
+            if rx > 0. && ry > 0. && t.is_similarity() {
+                return Ok(vec![Traced::Ellipse(
+                    t.apply(center),
+                    rx * t.scale(),
+                    ry * t.scale(),
+                    Angle::from_radians(t.b.atan2(t.a)),
+                )]);
+            }
+            builder.ellipse(center, rx, ry);
+        }
+        _ => {}
+    }
+    Ok(builder.finish())
+}
+
+/// Flattens paths to polylines, in pixels.
+struct PathBuilder {
+    transform: Transform,
+    tolerance: f64,
+    traced: Vec<Traced>,
+    /// Points of the current subpath, transformed
+    points: Vec<Vec2>,
+    /// Start and current point of the subpath, in user space
+    start: Vec2,
+    current: Vec2,
+}
+
+impl PathBuilder {
+    fn new(transform: Transform, tolerance: f64) -> Self {
+        Self {
+            transform,
+            tolerance,
+            traced: vec![],
+            points: vec![],
+            start: Vec2::ZERO,
+            current: Vec2::ZERO,
+        }
+    }
+
+    fn trace_path(&mut self, data: &Data) {
+        // Last control point of the previous command, if it was a cubic or a quadratic curve
+        let mut cubic: Option<Vec2> = None;
+        let mut quad: Option<Vec2> = None;
+        for command in data.iter() {
+            let (position, params) = match command {
+                Command::Close => {
+                    self.close();
+                    (cubic, quad) = (None, None);
+                    continue;
+                }
+                Command::Move(p, n)
+                | Command::Line(p, n)
+                | Command::HorizontalLine(p, n)
+                | Command::VerticalLine(p, n)
+                | Command::QuadraticCurve(p, n)
+                | Command::SmoothQuadraticCurve(p, n)
+                | Command::CubicCurve(p, n)
+                | Command::SmoothCubicCurve(p, n)
+                | Command::EllipticalArc(p, n) => (p, n),
+            };
+            let params = params.iter().map(|&n| n as f64).collect::<Vec<f64>>();
+            let relative = matches!(position, Position::Relative);
+            let arity = match command {
+                Command::HorizontalLine(..) | Command::VerticalLine(..) => 1,
+                Command::Move(..) | Command::Line(..) | Command::SmoothQuadraticCurve(..) => 2,
+                Command::QuadraticCurve(..) | Command::SmoothCubicCurve(..) => 4,
+                Command::CubicCurve(..) => 6,
+                _ => 7,
+            };
+            for (i, n) in params.chunks_exact(arity).enumerate() {
+                let origin = if relative { self.current } else { Vec2::ZERO };
+                let at = |j: usize| Vec2::new(n[j], n[j + 1]) + origin;
+                let reflect = |control: Option<Vec2>| match control {
+                    Some(c) => self.current * 2. - c,
+                    None => self.current,
+                };
+                let (mut next_cubic, mut next_quad) = (None, None);
+                match command {
+                    Command::Move(..) if i == 0 => self.move_to(at(0)),
+                    Command::Move(..) | Command::Line(..) => self.line_to(at(0)),
+                    Command::HorizontalLine(..) => {
+                        let x = if relative {
+                            self.current.x + n[0]
+                        } else {
+                            n[0]
+                        };
+                        self.line_to(Vec2::new(x, self.current.y));
+                    }
+                    Command::VerticalLine(..) => {
+                        let y = if relative {
+                            self.current.y + n[0]
+                        } else {
+                            n[0]
+                        };
+                        self.line_to(Vec2::new(self.current.x, y));
+                    }
+                    Command::CubicCurve(..) => {
+                        next_cubic = Some(at(2));
+                        self.cubic_to(at(0), at(2), at(4));
+                    }
+                    Command::SmoothCubicCurve(..) => {
+                        next_cubic = Some(at(0));
+                        self.cubic_to(reflect(cubic), at(0), at(2));
+                    }
+                    Command::QuadraticCurve(..) => {
+                        next_quad = Some(at(0));
+                        self.quad_to(at(0), at(2));
+                    }
+                    Command::SmoothQuadraticCurve(..) => {
+                        let control = reflect(quad);
+                        next_quad = Some(control);
+                        self.quad_to(control, at(0));
+                    }
+                    _ => self.arc_to(n[0], n[1], n[2], n[3] != 0., n[4] != 0., at(5)),
+                }
+                (cubic, quad) = (next_cubic, next_quad);
+            }
+        }
+    }
+
+    fn move_to(&mut self, p: Vec2) {
+        self.end_subpath(false);
+        self.start = p;
+        self.current = p;
+    }
+
+    fn line_to(&mut self, p: Vec2) {
+        self.begin_subpath();
+        self.points.push(self.transform.apply(p));
+        self.current = p;
+    }
+
+    fn cubic_to(&mut self, c1: Vec2, c2: Vec2, p: Vec2) {
+        self.begin_subpath();
+        let t = self.transform;
+        let p0 = t.apply(self.current);
+        Curve::Cubic(p0, t.apply(c1), t.apply(c2), t.apply(p))
+            .flatten_into(self.tolerance, &mut self.points);
+        self.current = p;
+    }
+
+    fn quad_to(&mut self, c: Vec2, p: Vec2) {
+        let p0 = self.current;
+        self.cubic_to(p0 + (c - p0) * (2. / 3.), p + (c - p) * (2. / 3.), p);
+    }
+
+    /// Draw an elliptical arc, following the SVG endpoint parameterization.
+    /// See <https://www.w3.org/TR/SVG11/implnote.html#ArcImplementationNotes>
+    fn arc_to(&mut self, rx: f64, ry: f64, rotation: f64, large: bool, sweep: bool, p: Vec2) {
+        let p1 = self.current;
+        if p1.distance(p) == 0. {
+            return;
+        }
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0. || ry == 0. {
+            self.line_to(p);
+            return;
+        }
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let (dx, dy) = ((p1.x - p.x) / 2., (p1.y - p.y) / 2.);
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1. {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let sign = if large == sweep { -1. } else { 1. };
+        let coef = sign * (numerator / denominator).max(0.).sqrt();
+        let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+        let center = Vec2::new(
+            cos * cx1 - sin * cy1 + (p1.x + p.x) / 2.,
+            sin * cx1 + cos * cy1 + (p1.y + p.y) / 2.,
+        );
+        let theta = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let mut delta = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - theta;
+        if sweep && delta < 0. {
+            delta += 2. * PI;
+        } else if !sweep && delta > 0. {
+            delta -= 2. * PI;
+        }
+
+        self.begin_subpath();
+        let segments = self.arc_segments(rx.max(ry), delta);
+        for i in 1..segments {
+            let angle = theta + delta * i as f64 / segments as f64;
+            let q = ellipse_point(center, rx, ry, (sin, cos), angle);
+            self.points.push(self.transform.apply(q));
+        }
+        self.points.push(self.transform.apply(p));
+        self.current = p;
+    }
+
+    fn ellipse(&mut self, center: Vec2, rx: f64, ry: f64) {
+        if rx <= 0. || ry <= 0. {
+            return;
+        }
+        let segments = self.arc_segments(rx.max(ry), 2. * PI);
+        self.move_to(center + Vec2::new(rx, 0.));
+        for i in 1..segments {
+            let angle = 2. * PI * i as f64 / segments as f64;
+            self.line_to(ellipse_point(center, rx, ry, (0., 1.), angle));
+        }
+        self.close();
+    }
+
+    /// Number of segments approximating an arc of `radius`, in user space, spanning `angle`.
+    fn arc_segments(&self, radius: f64, angle: f64) -> usize {
+        segments_for_tolerance(radius * self.transform.scale(), angle, self.tolerance)
+    }
+
+    fn begin_subpath(&mut self) {
+        if self.points.is_empty() {
+            self.points.push(self.transform.apply(self.current));
+        }
+    }
+
+    fn end_subpath(&mut self, closed: bool) {
+        let mut points = std::mem::take(&mut self.points);
+        if points.len() < 2 {
+            return;
+        }
+        if closed && points.len() > 3 && points[0].distance(points[points.len() - 1]) < 1e-9 {
+            points.pop();
+        }
+        if closed && points.len() > 2 {
+            self.traced.push(Traced::Closed(points, vec![]));
+        } else {
+            self.traced.push(Traced::Open(points));
+        }
+    }
+
+    fn close(&mut self) {
+        self.end_subpath(true);
+        self.current = self.start;
+    }
+
+    /// Return the traced shapes. Closed subpaths nested inside an odd number
+    /// of others become holes of the innermost one, as with the even-odd rule.
+    fn finish(&mut self) -> Vec<Traced> {
+        self.end_subpath(false);
+        let traced = std::mem::take(&mut self.traced);
+        let rings = traced
+            .iter()
+            .filter_map(|t| match t {
+                Traced::Closed(points, _) => Some(points.clone()),
+                _ => None,
+            })
+            .collect::<Vec<Vec<Vec2>>>();
+        if rings.len() < 2 {
+            return traced;
+        }
+        // Rings containing each ring, from the outermost to the innermost
+        let parents = rings
+            .iter()
+            .enumerate()
+            .map(|(i, ring)| {
+                let mut parents = (0..rings.len())
+                    .filter(|&j| j != i && inside(ring[0], &rings[j]))
+                    .collect::<Vec<usize>>();
+                parents.sort_by(|&a, &b| area(&rings[b]).total_cmp(&area(&rings[a])));
+                parents
+            })
+            .collect::<Vec<Vec<usize>>>();
+        let mut holes: Vec<Vec<Vec<Vec2>>> = vec![vec![]; rings.len()];
+        for (i, ring) in rings.iter().enumerate() {
+            if parents[i].len() % 2 == 1 {
+                holes[parents[i][parents[i].len() - 1]].push(ring.clone());
+            }
+        }
+        let mut ring = 0;
+        let mut shapes = vec![];
+        for t in traced {
+            match t {
+                Traced::Closed(points, _) => {
+                    if parents[ring].len() % 2 == 0 {
+                        shapes.push(Traced::Closed(points, std::mem::take(&mut holes[ring])));
+                    }
+                    ring += 1;
+                }
+                t => shapes.push(t),
+            }
+        }
+        shapes
+    }
+}
+
+/// Return whether `p` is inside `ring`, using the even-odd rule.
+fn inside(p: Vec2, ring: &[Vec2]) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn area(ring: &[Vec2]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        .abs()
+        / 2.
+}
+
+fn ellipse_point(center: Vec2, rx: f64, ry: f64, (sin, cos): (f64, f64), angle: f64) -> Vec2 {
+    let (x, y) = (rx * angle.cos(), ry * angle.sin());
+    Vec2::new(center.x + cos * x - sin * y, center.y + sin * x + cos * y)
+}
+
+/// Return the value of a presentation attribute, giving precedence to the `style` attribute.
+fn property(attributes: &Attributes, name: &str) -> Option<String> {
+    let declarations = attributes
+        .get("style")
+        .map(|style| {
+            style
+                .split(';')
+                .filter_map(|d| d.split_once(':'))
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .collect::<HashMap<String, String>>()
+        })
+        .unwrap_or_default();
+    declarations
+        .get(name)
+        .cloned()
+        .or_else(|| attributes.get(name).map(|v| v.trim().to_string()))
+        .filter(|v| v != "inherit")
+}
+
+fn length(attributes: &Attributes, name: &str) -> Option<f64> {
+    attributes.get(name).and_then(|v| Uom::parse_css_length(v))
+}
+
+/// Parse a list of numbers separated by whitespace and/or commas, like `10,20 -5.5e1-.5`.
+fn parse_numbers(list: &str) -> Option<Vec<f64>> {
+    let bytes = list.as_bytes();
+    let mut numbers = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() || bytes[i] == b',' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if matches!(bytes[i], b'+' | b'-') {
+            i += 1;
+        }
+        let mut dot = false;
+        while i < bytes.len() && (bytes[i].is_ascii_digit() || (bytes[i] == b'.' && !dot)) {
+            dot |= bytes[i] == b'.';
+            i += 1;
+        }
+        if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+            i += 1;
+            if i < bytes.len() && matches!(bytes[i], b'+' | b'-') {
+                i += 1;
+            }
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        numbers.push(list[start..i].parse::<f64>().ok()?);
+    }
+    Some(numbers)
+}
+
+fn round(n: f64) -> f64 {
+    (n * 1000.).round() / 1000.
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::{parse_numbers, parse_svg, Transform};
+    use crate::prelude::*;
+    use crate::Shape;
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    fn svg(content: &str) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"
+                xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">{content}</svg>"#
+        )
+    }
+
+    fn points(shape: &Shape) -> Vec<Vec2> {
+        match shape {
+            Shape::LineString(l) => l.points.clone(),
+            Shape::Polygon(p) => p.points.clone(),
+            _ => panic!("Expected a LineString or a Polygon"),
+        }
+    }
+
+    #[rstest]
+    #[case("10,20 -5.5e1-.5", vec![10., 20., -55., -0.5])]
+    #[case(" 1.5.5 ", vec![1.5, 0.5])]
+    #[case("", vec![])]
+    fn numbers(#[case] list: &str, #[case] expected: Vec<f64>) {
+        assert_eq!(parse_numbers(list), Some(expected));
+    }
+
+    #[test]
+    fn transform_list() {
+        let t = Transform::parse("translate(10, 20) rotate(90) scale(2)").unwrap();
+        let p = t.apply(Vec2::new(1., 0.));
+        assert_relative_eq!(p.x, 10., epsilon = 1e-9);
+        assert_relative_eq!(p.y, 22., epsilon = 1e-9);
+        assert_eq!(Transform::parse("wobble(3)"), None);
+    }
+
+    #[test]
+    fn layers_become_groups() {
+        let content = svg(r#"
+            <line x1="0" y1="0" x2="10" y2="0" stroke="red"/>
+            <g inkscape:groupmode="layer" style="stroke:blue;stroke-width:2">
+                <polyline points="0,0 10,0 10,10"/>
+                <rect x="5" y="5" width="10" height="10"/>
+            </g>
+            <g inkscape:groupmode="layer" transform="scale(2)">
+                <path d="M1 1 L2 1" stroke="green" stroke-width="0.5mm"/>
+            </g>
+            <defs><circle r="5"/></defs>"#);
+        let groups = parse_svg(&content, Uom::Px, 0.1).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert!(groups[0].style == Style::new("red", "1px"));
+        assert!(groups[1].style == Style::new("blue", "2px"));
+        assert!(groups[2].style == Style::new("green", "3.78px"));
+        assert_eq!(groups[1].elements.len(), 2);
+        assert_eq!(points(&groups[1].elements[1]).len(), 4);
+        assert_eq!(
+            points(&groups[2].elements[0]),
+            vec![Vec2::new(2., 2.), Vec2::new(4., 2.)]
+        );
+    }
+
+    #[test]
+    fn view_box_and_units() {
+        let content = r#"<svg xmlns="http://www.w3.org/2000/svg"
+            width="100mm" height="50mm" viewBox="0 0 200 100">
+            <path d="m 20,10 h 100 v 50 z"/></svg>"#;
+        let groups = parse_svg(content, Uom::Mm, 0.1).unwrap();
+        let Shape::Polygon(p) = &groups[0].elements[0] else {
+            panic!("Expected a Polygon");
+        };
+        let expected = [Vec2::new(10., 5.), Vec2::new(60., 5.), Vec2::new(60., 30.)];
+        for (p, e) in p.points.iter().zip(expected) {
+            assert_relative_eq!(p.x, e.x, epsilon = 1e-4);
+            assert_relative_eq!(p.y, e.y, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn curves_are_flattened_within_tolerance() {
+        let content = svg(r#"<path d="M 0,0 C 0,50 100,50 100,0 S 200,-50 200,0"/>
+            <path d="M 0,0 A 50,50 0 0 1 100,0"/>"#);
+        let groups = parse_svg(&content, Uom::Px, 0.05).unwrap();
+        let bezier = points(&groups[0].elements[0]);
+        assert!(bezier.len() > 10);
+        assert_eq!(bezier[bezier.len() - 1], Vec2::new(200., 0.));
+        // Points of the first curve, symmetric around x = 50, peak at y = 37.5
+        let peak = bezier.iter().map(|p| p.y).fold(f64::MIN, f64::max);
+        assert!(peak <= 37.5 && peak > 37.45);
+
+        let arc = points(&groups[0].elements[1]);
+        assert_eq!(arc[arc.len() - 1], Vec2::new(100., 0.));
+        for p in arc {
+            assert_relative_eq!(p.distance(Vec2::new(50., 0.)), 50., epsilon = 1e-9);
+            assert!(p.y <= 1e-9);
+        }
+    }
+
+    #[test]
+    fn nested_subpaths_become_holes() {
+        let content = svg(r#"<path d="M0,0 H30 V30 H0 Z M10,10 H20 V20 H10 Z
+            M12,12 H18 V18 H12 Z M40,0 H50 V10 H40 Z"/>"#);
+        let groups = parse_svg(&content, Uom::Px, 0.1).unwrap();
+        let polygons = groups[0]
+            .elements
+            .iter()
+            .map(|e| match e {
+                Shape::Polygon(p) => (p.points[0], p.interiors.len()),
+                _ => panic!("Expected a Polygon"),
+            })
+            .collect::<Vec<(Vec2, usize)>>();
+        assert_eq!(
+            polygons,
+            vec![
+                (Vec2::new(0., 0.), 1),
+                (Vec2::new(12., 12.), 0),
+                (Vec2::new(40., 0.), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn circles_are_kept_unless_distorted() {
+        let content = svg(r#"<circle cx="10" cy="10" r="5" transform="rotate(45)"/>
+            <circle cx="10" cy="10" r="5" transform="scale(2, 1)"/>
+            <ellipse rx="4" ry="2" transform="rotate(30) scale(2)"/>"#);
+        let groups = parse_svg(&content, Uom::Px, 0.1).unwrap();
+        let Shape::Circle(c) = &groups[0].elements[0] else {
+            panic!("Expected a Circle");
+        };
+        assert_relative_eq!(c.radius, 5.);
+        assert_relative_eq!(c.center.x, 0., epsilon = 1e-9);
+        assert!(matches!(groups[0].elements[1], Shape::Polygon(_)));
+        let Shape::Ellipse(e) = &groups[0].elements[2] else {
+            panic!("Expected an Ellipse");
+        };
+        assert_relative_eq!(e.rx, 8., epsilon = 1e-9);
+        assert_relative_eq!(e.rotation.to_degrees(), 30., epsilon = 1e-9);
+    }
+}
@@ -1,14 +1,23 @@
 pub mod angle;
+pub mod contour;
 pub mod field;
+pub mod flow;
 pub mod grid;
 pub mod group;
+pub mod import;
 pub mod layout;
+pub mod occlusion;
+pub mod optimize;
 pub mod pen;
 pub mod prelude;
+pub mod raster;
 pub mod render;
+pub mod ridgeline;
+pub mod sdf;
 pub mod seed;
 pub mod shapes;
 pub mod sketch;
+pub mod stats;
 pub mod style;
 pub mod traits;
 pub mod uom;
@@ -20,7 +29,10 @@ use vec2::Vec2;
 
 #[derive(Clone)]
 pub enum Shape {
+    Arc(shapes::arc::Arc),
+    Bezier(shapes::bezier::Bezier),
     Circle(shapes::circle::Circle),
+    Ellipse(shapes::ellipse::Ellipse),
     Rectangle(shapes::rectangle::Rect),
     Hexagon(shapes::hexagon::Hexagon),
     LineString(shapes::linestring::LineString),
@@ -30,17 +42,74 @@ pub enum Shape {
 }
 
 impl Shape {
+    /// Return the point where the pen touches the paper to draw the shape.
     pub fn path_start(&self) -> Vec2 {
         match self {
-            Shape::Circle(s) => s.center,
+            Shape::Arc(s) => s.start_point(),
+            Shape::Bezier(s) => s.start(),
+            Shape::Circle(s) => s.center + Vec2::new(s.radius, 0.),
+            Shape::Ellipse(s) => s.point_at(angle::Angle::zero()),
             Shape::Rectangle(s) => s.xy,
-            Shape::Hexagon(s) => s.center,
+            Shape::Hexagon(s) => s.vertexes()[0],
             Shape::LineString(s) => *s.points.first().unwrap(),
             Shape::Polygon(s) => *s.points.first().unwrap(),
             Shape::Text(s) => s.pos,
             Shape::Triangle(s) => s.a,
         }
     }
+
+    /// Return the point where the pen leaves the paper after drawing the shape.
+    pub fn path_end(&self) -> Vec2 {
+        match self {
+            Shape::Arc(s) => s.end_point(),
+            Shape::Bezier(s) => s.end(),
+            Shape::LineString(s) => *s.points.last().unwrap(),
+            _ => self.path_start(),
+        }
+    }
+
+    /// Return whether drawing the shape backwards changes its start and end points.
+    pub fn is_reversible(&self) -> bool {
+        matches!(
+            self,
+            Shape::Arc(_) | Shape::Bezier(_) | Shape::LineString(_)
+        )
+    }
+
+    /// Return the shape drawn backwards.
+    pub fn reverse(&self) -> Shape {
+        match self {
+            Shape::Arc(s) => Shape::Arc(s.reverse()),
+            Shape::Bezier(s) => Shape::Bezier(s.reverse()),
+            Shape::LineString(s) => Shape::LineString(shapes::linestring::LineString::new(
+                s.points.iter().rev().copied().collect(),
+            )),
+            _ => self.clone(),
+        }
+    }
+
+    /// Approximate the shape with polylines, as a pen would trace it.
+    ///
+    /// Curves are subdivided so that the approximation never deviates from
+    /// the original by more than `tolerance`. Closed shapes return closed
+    /// polylines (first point equal to the last one) and `Text` returns
+    /// nothing, as it has no geometry of its own.
+    pub fn flatten(&self, tolerance: f64) -> Vec<shapes::linestring::LineString> {
+        match self {
+            Shape::Arc(s) => vec![s.to_linestring(tolerance)],
+            Shape::Bezier(s) => vec![s.flatten(tolerance)],
+            Shape::Circle(s) => vec![s
+                .to_polygon(s.points_for_tolerance(tolerance))
+                .to_linestring()],
+            Shape::Ellipse(s) => vec![s.to_linestring(tolerance)],
+            Shape::Rectangle(s) => vec![s.to_linestr(true)],
+            Shape::Hexagon(s) => vec![s.to_polygon().to_linestring()],
+            Shape::LineString(s) => vec![s.clone()],
+            Shape::Polygon(s) => s.rings(),
+            Shape::Text(_) => vec![],
+            Shape::Triangle(s) => vec![s.to_polygon().to_linestring()],
+        }
+    }
 }
 
 pub fn map_range(n: f64, start1: f64, stop1: f64, start2: f64, stop2: f64) -> f64 {
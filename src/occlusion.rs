@@ -0,0 +1,187 @@
+//! Hidden-line removal between shapes drawn on top of each other
+
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::traits::ToGeoPolygon;
+use crate::Shape;
+use geo::BoundingRect;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{Envelope as _, RTree, AABB};
+
+// The bounding box of a mask in the spatial index, with its position
+type Envelope = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// The strokes drawing a shape, and the area it hides from the shapes
+/// behind it, if any.
+#[derive(Clone, PartialEq)]
+pub struct Occluder {
+    /// The area hidden by the shape, `None` when it hides nothing
+    pub mask: Option<Polygon>,
+    /// The strokes drawing the shape
+    pub strokes: Vec<LineString>,
+}
+
+impl Occluder {
+    pub fn new(mask: Polygon, strokes: Vec<LineString>) -> Self {
+        Self {
+            mask: Some(mask),
+            strokes,
+        }
+    }
+
+    /// Return an occluder hiding nothing, whose strokes can still be hidden.
+    pub fn transparent(strokes: Vec<LineString>) -> Self {
+        Self {
+            mask: None,
+            strokes,
+        }
+    }
+}
+
+/// Remove the parts of the strokes of `occluders`, given front to back,
+/// hidden behind the masks of the ones before them. Return the visible
+/// strokes of each occluder, in the same order.
+pub fn occlude(occluders: &[Occluder]) -> Vec<Vec<LineString>> {
+    let masks = occluders
+        .iter()
+        .map(|o| {
+            let rect = o.mask.as_ref()?.to_geo_polygon().bounding_rect()?;
+            Some(AABB::from_corners(
+                [rect.min().x, rect.min().y],
+                [rect.max().x, rect.max().y],
+            ))
+        })
+        .collect::<Vec<Option<AABB<[f64; 2]>>>>();
+    let tree = RTree::bulk_load(
+        masks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| {
+                m.map(|m| Envelope::new(Rectangle::from_corners(m.lower(), m.upper()), i))
+            })
+            .collect(),
+    );
+    occluders
+        .iter()
+        .enumerate()
+        .map(|(i, occluder)| {
+            occluder
+                .strokes
+                .iter()
+                .flat_map(|stroke| {
+                    let mut front = tree
+                        .locate_in_envelope_intersecting(&envelope(stroke))
+                        .map(|e| e.data)
+                        .filter(|j| *j < i)
+                        .collect::<Vec<usize>>();
+                    front.sort_unstable();
+                    let mut visible = vec![stroke.clone()];
+                    for j in front {
+                        let (mask, bounds) =
+                            (occluders[j].mask.as_ref().unwrap(), masks[j].unwrap());
+                        visible = visible
+                            .into_iter()
+                            .flat_map(|s| {
+                                if envelope(&s).intersects(&bounds) {
+                                    s.clip(mask, true)
+                                } else {
+                                    vec![s]
+                                }
+                            })
+                            .collect();
+                    }
+                    visible
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Remove the parts of the `elements`, given front to back, hidden behind
+/// the closed shapes before them. Hidden shapes are replaced with the
+/// polylines, within `tolerance`, of their visible parts.
+pub fn occlude_shapes(elements: Vec<Shape>, tolerance: f64) -> Vec<Shape> {
+    let occluders = elements
+        .iter()
+        .map(|e| {
+            let strokes = e.flatten(tolerance);
+            match e {
+                Shape::Polygon(p) => Occluder::new(p.clone(), strokes),
+                Shape::Circle(_)
+                | Shape::Ellipse(_)
+                | Shape::Rectangle(_)
+                | Shape::Hexagon(_)
+                | Shape::Triangle(_) => {
+                    let mut ring = strokes[0].points.clone();
+                    ring.pop();
+                    Occluder::new(Polygon::new(ring), strokes)
+                }
+                Shape::Arc(_) | Shape::Bezier(_) | Shape::LineString(_) | Shape::Text(_) => {
+                    Occluder::transparent(strokes)
+                }
+            }
+        })
+        .collect::<Vec<Occluder>>();
+    let visible = occlude(&occluders);
+    elements
+        .into_iter()
+        .zip(occluders)
+        .zip(visible)
+        .flat_map(|((element, occluder), visible)| {
+            if visible == occluder.strokes {
+                vec![element]
+            } else {
+                visible.into_iter().map(Shape::LineString).collect()
+            }
+        })
+        .collect()
+}
+
+/// Return the bounding box of `line` in the spatial index.
+fn envelope(line: &LineString) -> AABB<[f64; 2]> {
+    let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+    for p in &line.points {
+        min = [min[0].min(p.x), min[1].min(p.y)];
+        max = [max[0].max(p.x), max[1].max(p.y)];
+    }
+    AABB::from_corners(min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::traits::ToShape;
+
+    #[test]
+    fn front_shapes_hide_the_ones_behind() {
+        let front = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let back = LineString::line(Vec2::new(-5., 5.), Vec2::new(15., 5.));
+        let visible = occlude(&[
+            Occluder::new(front.to_polygon(), vec![front.to_linestr(true)]),
+            Occluder::transparent(vec![back]),
+        ]);
+        assert_eq!(visible[0].len(), 1);
+        assert_eq!(visible[1].len(), 2);
+        assert!(visible[1]
+            .iter()
+            .flat_map(|l| l.points.iter())
+            .all(|p| p.x <= 0. || p.x >= 10.));
+    }
+
+    #[test]
+    fn only_hidden_shapes_become_linestrings() {
+        let elements = vec![
+            Circle::new(Vec2::new(0., 0.), 5.).to_shape(),
+            Rect::new(Vec2::new(0., 0.), 10., 10.).to_shape(),
+            Circle::new(Vec2::new(30., 30.), 5.).to_shape(),
+        ];
+        let occluded = occlude_shapes(elements, 0.01);
+        assert!(matches!(occluded[0], Shape::Circle(_)));
+        // The quarter of the rectangle outline inside the circle is hidden
+        assert!(occluded[1..occluded.len() - 1]
+            .iter()
+            .all(|s| matches!(s, Shape::LineString(_))));
+        assert!(matches!(occluded[occluded.len() - 1], Shape::Circle(_)));
+    }
+}
@@ -0,0 +1,372 @@
+//! Passes reducing the time a plotter spends drawing a sketch
+
+pub mod dedup;
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::vec2::Vec2;
+use crate::Shape;
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+
+// How many positions ahead 2-opt looks for a block to reverse
+const TWO_OPT_WINDOW: usize = 32;
+// Maximum number of 2-opt improvement passes
+const TWO_OPT_PASSES: usize = 8;
+
+/// The outcome of a path ordering pass.
+pub struct PathOrderReport {
+    /// Pen-up travel before the pass.
+    pub travel_before: f64,
+    /// Pen-up travel after the pass.
+    pub travel_after: f64,
+    /// Time spent running the pass.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for PathOrderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let saved = if self.travel_before > 0. {
+            (1. - self.travel_after / self.travel_before) * 100.
+        } else {
+            0.
+        };
+        write!(
+            f,
+            "pen-up travel {:.1} -> {:.1} ({:.1}% saved) in {} milliseconds",
+            self.travel_before,
+            self.travel_after,
+            saved,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Compute the distance travelled with the pen up to draw `elements` in order,
+/// starting from the origin.
+pub fn pen_up_distance(elements: &[Shape]) -> f64 {
+    let mut position = Vec2::ZERO;
+    let mut distance = 0.;
+    for e in elements {
+        distance += position.distance(e.path_start());
+        position = e.path_end();
+    }
+    distance
+}
+
+/// Reorder, and reverse where possible, `elements` to reduce pen-up travel.
+///
+/// A greedy nearest-neighbour tour, starting from the origin, is built with
+/// a spatial index over both endpoints of every path. The tour is then
+/// refined with 2-opt moves, reversing blocks of up to `TWO_OPT_WINDOW` paths.
+pub fn optimize_path_order(elements: Vec<Shape>) -> Vec<Shape> {
+    let mut tour = nearest_neighbour_tour(&elements);
+    two_opt(&mut tour);
+    let mut elements = elements
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<Option<Shape>>>();
+    tour.iter()
+        .map(|step| {
+            let shape = elements[step.index].take().unwrap();
+            if step.reversed {
+                shape.reverse()
+            } else {
+                shape
+            }
+        })
+        .collect()
+}
+
+/// A path as visited by a tour.
+struct Step {
+    index: usize,
+    reversed: bool,
+    start: Vec2,
+    end: Vec2,
+}
+
+impl Step {
+    fn flip(&mut self) {
+        self.reversed = !self.reversed;
+        std::mem::swap(&mut self.start, &mut self.end);
+    }
+}
+
+// An endpoint in the spatial index, tagged with the path index and
+// whether entering the path there means drawing it reversed.
+type Endpoint = GeomWithData<[f64; 2], (usize, bool)>;
+
+fn nearest_neighbour_tour(elements: &[Shape]) -> Vec<Step> {
+    let mut endpoints = vec![];
+    for (index, e) in elements.iter().enumerate() {
+        let start = e.path_start();
+        endpoints.push(Endpoint::new([start.x, start.y], (index, false)));
+        if e.is_reversible() {
+            let end = e.path_end();
+            endpoints.push(Endpoint::new([end.x, end.y], (index, true)));
+        }
+    }
+    let mut tree = RTree::bulk_load(endpoints);
+
+    let mut tour = Vec::with_capacity(elements.len());
+    let mut position = Vec2::ZERO;
+    while let Some(nearest) = tree.nearest_neighbor(&[position.x, position.y]) {
+        let (index, reversed) = nearest.data;
+        let e = &elements[index];
+        let (start, end) = (e.path_start(), e.path_end());
+        tree.remove(&Endpoint::new([start.x, start.y], (index, false)));
+        if e.is_reversible() {
+            tree.remove(&Endpoint::new([end.x, end.y], (index, true)));
+        }
+        let mut step = Step {
+            index,
+            reversed: false,
+            start,
+            end,
+        };
+        if reversed {
+            step.flip();
+        }
+        position = step.end;
+        tour.push(step);
+    }
+    tour
+}
+
+// Reversing a block of the tour is always legal: paths that can't be
+// reversed are closed, so their start and end points coincide.
+fn two_opt(tour: &mut [Step]) {
+    let n = tour.len();
+    for _ in 0..TWO_OPT_PASSES {
+        let mut improved = false;
+        for i in 0..n {
+            for j in i + 1..usize::min(n, i + TWO_OPT_WINDOW) {
+                let before = if i == 0 { Vec2::ZERO } else { tour[i - 1].end };
+                let mut delta = before.distance(tour[j].end) - before.distance(tour[i].start);
+                if j + 1 < n {
+                    let after = tour[j + 1].start;
+                    delta += tour[i].start.distance(after) - tour[j].end.distance(after);
+                }
+                if delta < -1e-9 {
+                    tour[i..=j].reverse();
+                    tour[i..=j].iter_mut().for_each(|s| s.flip());
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+/// The outcome of a line merging pass.
+pub struct MergeReport {
+    /// Pen lifts before the pass.
+    pub pen_lifts_before: usize,
+    /// Pen lifts after the pass.
+    pub pen_lifts_after: usize,
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pen lifts {} -> {}",
+            self.pen_lifts_before, self.pen_lifts_after
+        )
+    }
+}
+
+/// Count how many times the pen is lifted to draw `elements` in order.
+///
+/// No lift is needed between two paths when the second one starts
+/// where the first one ends.
+pub fn pen_lifts(elements: &[Shape]) -> usize {
+    let mut position: Option<Vec2> = None;
+    let mut lifts = 0;
+    for e in elements {
+        if matches!(e, Shape::Text(_)) {
+            continue;
+        }
+        if position.is_none_or(|p| p.distance(e.path_start()) > 1e-9) {
+            lifts += 1;
+        }
+        position = Some(e.path_end());
+    }
+    lifts
+}
+
+/// Join `LineString`s whose endpoints are closer than `tolerance`, reversing
+/// them as needed. Chains whose start meets their end become `Polygon`s.
+///
+/// Elements other than `LineString`s are left untouched.
+pub fn merge_lines(elements: Vec<Shape>, tolerance: f64) -> Vec<Shape> {
+    let lines = elements
+        .iter()
+        .map(|e| match e {
+            Shape::LineString(s) if s.points.len() > 1 => Some(s),
+            _ => None,
+        })
+        .collect::<Vec<Option<&LineString>>>();
+
+    let mut endpoints = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(line) = line {
+            let (start, end) = (line.points[0], *line.points.last().unwrap());
+            endpoints.push(Endpoint::new([start.x, start.y], (index, false)));
+            endpoints.push(Endpoint::new([end.x, end.y], (index, true)));
+        }
+    }
+    let mut tree = RTree::bulk_load(endpoints);
+    let mut used = vec![false; elements.len()];
+
+    let remove = |tree: &mut RTree<Endpoint>, index: usize| {
+        let line = lines[index].unwrap();
+        let (start, end) = (line.points[0], *line.points.last().unwrap());
+        tree.remove(&Endpoint::new([start.x, start.y], (index, false)));
+        tree.remove(&Endpoint::new([end.x, end.y], (index, true)));
+    };
+    // Find the closest unused line touching `point`, and whether it touches with its end
+    let closest = |tree: &RTree<Endpoint>, point: Vec2| {
+        tree.locate_within_distance([point.x, point.y], tolerance * tolerance)
+            .min_by(|a, b| {
+                let da = point.distance_squared(Vec2::from_slice(a.geom()));
+                let db = point.distance_squared(Vec2::from_slice(b.geom()));
+                da.total_cmp(&db)
+            })
+            .map(|e| e.data)
+    };
+
+    let mut merged = vec![];
+    for (index, element) in elements.iter().enumerate() {
+        let Some(line) = lines[index] else {
+            merged.push(element.clone());
+            continue;
+        };
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+        remove(&mut tree, index);
+
+        let mut points = line.points.clone();
+        // Extend forward from the end, then backward from the start
+        for forward in [true, false] {
+            if !forward {
+                points.reverse();
+            }
+            while let Some((other, at_end)) = closest(&tree, *points.last().unwrap()) {
+                used[other] = true;
+                remove(&mut tree, other);
+                let other = &lines[other].unwrap().points;
+                if at_end {
+                    points.extend(other.iter().rev().skip(1));
+                } else {
+                    points.extend(other.iter().skip(1));
+                }
+            }
+        }
+        points.reverse();
+
+        let closed = points.len() > 3 && points[0].distance(*points.last().unwrap()) <= tolerance;
+        if closed && points.len() > lines[index].unwrap().points.len() {
+            points.pop();
+            merged.push(Shape::Polygon(Polygon::new(points)));
+        } else {
+            merged.push(Shape::LineString(LineString::new(points)));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::optimize::{merge_lines, optimize_path_order, pen_lifts, pen_up_distance};
+    use crate::prelude::*;
+    use crate::traits::ToShape;
+    use crate::Shape;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn reverses_linestrings() {
+        let elements = vec![
+            LineString::line(Vec2::new(10., 0.), Vec2::new(20., 0.)).to_shape(),
+            LineString::line(Vec2::new(10., 1.), Vec2::new(0., 1.)).to_shape(),
+        ];
+        assert_relative_eq!(pen_up_distance(&elements), 20.04988, epsilon = 0.0001);
+        let optimized = optimize_path_order(elements);
+        assert_relative_eq!(pen_up_distance(&optimized), 2., epsilon = 0.0001);
+        assert_eq!(optimized[0].path_start(), Vec2::new(0., 1.));
+        assert_eq!(optimized[1].path_start(), Vec2::new(10., 0.));
+    }
+
+    #[test]
+    fn keeps_every_element() {
+        let elements = (0..500)
+            .map(|i| {
+                let x = ((i * 7919) % 500) as f64;
+                let y = ((i * 104729) % 300) as f64;
+                LineString::line(Vec2::new(x, y), Vec2::new(x + 3., y + 1.)).to_shape()
+            })
+            .chain((0..50).map(|i| Circle::new(Vec2::new(i as f64 * 10., 5.), 2.).to_shape()))
+            .collect::<Vec<Shape>>();
+        let before = pen_up_distance(&elements);
+        let optimized = optimize_path_order(elements.clone());
+        assert_eq!(optimized.len(), elements.len());
+        assert!(pen_up_distance(&optimized) < before);
+        let circles = optimized
+            .iter()
+            .filter(|e| matches!(e, Shape::Circle(_)))
+            .count();
+        assert_eq!(circles, 50);
+    }
+
+    #[test]
+    fn merges_touching_linestrings() {
+        let elements = vec![
+            LineString::line(Vec2::new(20., 0.), Vec2::new(10., 0.)).to_shape(),
+            LineString::line(Vec2::new(20., 0.001), Vec2::new(30., 5.)).to_shape(),
+            Circle::new(Vec2::new(50., 50.), 3.).to_shape(),
+            LineString::line(Vec2::new(0., 0.), Vec2::new(10., 0.)).to_shape(),
+        ];
+        assert_eq!(pen_lifts(&elements), 4);
+        let merged = merge_lines(elements, 0.01);
+        assert_eq!(pen_lifts(&merged), 2);
+        let Shape::LineString(line) = &merged[0] else {
+            panic!("expected a LineString");
+        };
+        assert_eq!(
+            line.points,
+            vec![
+                Vec2::new(30., 5.),
+                Vec2::new(20., 0.),
+                Vec2::new(10., 0.),
+                Vec2::new(0., 0.)
+            ]
+        );
+    }
+
+    #[test]
+    fn closes_loops_into_polygons() {
+        let corners = [
+            Vec2::new(0., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 10.),
+            Vec2::new(0., 10.),
+        ];
+        let elements = (0..4)
+            .map(|i| LineString::line(corners[i], corners[(i + 1) % 4]).to_shape())
+            .collect::<Vec<Shape>>();
+        let merged = merge_lines(elements, 0.01);
+        assert_eq!(merged.len(), 1);
+        let Shape::Polygon(polygon) = &merged[0] else {
+            panic!("expected a Polygon");
+        };
+        assert_eq!(polygon.points.len(), 4);
+    }
+}
@@ -0,0 +1,252 @@
+use std::f64::consts::PI;
+
+use crate::angle::Angle;
+use crate::optimize::merge_lines;
+use crate::shapes::circle::Circle;
+use crate::shapes::linestring::LineString;
+use crate::vec2::Vec2;
+use crate::Shape;
+
+/// A straight piece of a path, with the layer it belongs to and
+/// its direction, in radians, in the `[0, PI)` range.
+struct Segment {
+    layer: usize,
+    angle: f64,
+    a: Vec2,
+    b: Vec2,
+}
+
+/// Remove the segments drawn more than once across `layers`, then re-chain
+/// what is left into as few paths as possible.
+///
+/// Every path is split into straight segments: segments lying on the same
+/// line (within `tolerance`) are merged, so that exact duplicates as well
+/// as partially overlapping ones are traced only once. When a segment is
+/// shared by several layers, it is kept in the first one. Circles are kept
+/// unless an identical one was already seen, text is left untouched.
+pub fn remove_duplicate_segments(layers: Vec<Vec<Shape>>, tolerance: f64) -> Vec<Vec<Shape>> {
+    let mut segments = vec![];
+    let mut others: Vec<Vec<Shape>> = vec![vec![]; layers.len()];
+    let mut circles: Vec<Circle> = vec![];
+    let mut longest: f64 = 0.;
+
+    for (layer, elements) in layers.iter().enumerate() {
+        for e in elements {
+            match e {
+                Shape::Circle(c) => {
+                    let duplicate = circles.iter().any(|o| {
+                        o.center.distance(c.center) <= tolerance
+                            && (o.radius - c.radius).abs() <= tolerance
+                    });
+                    if !duplicate {
+                        circles.push(*c);
+                        others[layer].push(e.clone());
+                    }
+                }
+                Shape::Text(_) => others[layer].push(e.clone()),
+                _ => {
+                    for line in e.flatten(tolerance) {
+                        for pair in line.points.windows(2) {
+                            let (a, b) = (pair[0], pair[1]);
+                            if a.distance(b) <= tolerance {
+                                continue;
+                            }
+                            longest = longest.max(a.distance(b));
+                            segments.push(Segment {
+                                layer,
+                                angle: a.angle_between(b).to_radians() % PI,
+                                a,
+                                b,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Two segments are collinear within `tolerance` if their directions differ
+    // at most by this angle, and their ends lie within `tolerance` of the same line.
+    let angle_tolerance = tolerance / longest.max(tolerance);
+    segments.sort_by(|s1, s2| s1.angle.total_cmp(&s2.angle));
+    let angles = segments.iter().map(|s| s.angle).collect::<Vec<f64>>();
+    let window = |from: f64, to: f64| {
+        angles.partition_point(|a| *a < from)..angles.partition_point(|a| *a <= to)
+    };
+
+    let mut merged = vec![false; segments.len()];
+    let mut kept: Vec<Vec<Shape>> = vec![vec![]; layers.len()];
+    for (i, reference) in segments.iter().enumerate() {
+        if merged[i] {
+            continue;
+        }
+        let direction = Vec2::from_polar(Angle::from_radians(reference.angle), 1.);
+        let normal = Vec2::new(-direction.y, direction.x);
+        let offset = (normal.dot(reference.a) + normal.dot(reference.b)) / 2.;
+
+        // Layer and interval covered along `direction` of the segments on the
+        // line of `reference`, looking up directions close to PI around zero
+        let (from, to) = (
+            reference.angle - angle_tolerance,
+            reference.angle + angle_tolerance,
+        );
+        let mut candidates = vec![window(from, to)];
+        if from < 0. {
+            candidates.push(window(from + PI, PI));
+        }
+        if to >= PI {
+            candidates.push(window(0., to - PI));
+        }
+        let mut collinear = vec![];
+        for j in candidates.into_iter().flatten() {
+            let s = &segments[j];
+            let on_line = |p: Vec2| (normal.dot(p) - offset).abs() <= tolerance;
+            if merged[j] || !on_line(s.a) || !on_line(s.b) {
+                continue;
+            }
+            merged[j] = true;
+            let (t1, t2) = (direction.dot(s.a), direction.dot(s.b));
+            collinear.push((s.layer, t1.min(t2), t1.max(t2)));
+        }
+
+        let mut covered: Vec<(f64, f64)> = vec![];
+        for (layer, kept) in kept.iter_mut().enumerate() {
+            let drawn = union(
+                collinear
+                    .iter()
+                    .filter(|l| l.0 == layer)
+                    .map(|l| (l.1, l.2))
+                    .collect(),
+                tolerance,
+            );
+            if drawn.is_empty() {
+                continue;
+            }
+            for (t1, t2) in subtract(&drawn, &covered, tolerance) {
+                kept.push(Shape::LineString(LineString::line(
+                    normal * offset + direction * t1,
+                    normal * offset + direction * t2,
+                )));
+            }
+            covered.extend(drawn);
+            covered = union(covered, tolerance);
+        }
+    }
+
+    kept.into_iter()
+        .zip(others)
+        .map(|(segments, mut others)| {
+            let mut elements = merge_lines(segments, tolerance);
+            elements.append(&mut others);
+            elements
+        })
+        .collect()
+}
+
+/// Merge intervals overlapping, or less than `tolerance` apart.
+fn union(mut intervals: Vec<(f64, f64)>, tolerance: f64) -> Vec<(f64, f64)> {
+    intervals.sort_by(|i1, i2| i1.0.total_cmp(&i2.0));
+    let mut merged: Vec<(f64, f64)> = vec![];
+    for (t1, t2) in intervals {
+        match merged.last_mut() {
+            Some(last) if t1 <= last.1 + tolerance => last.1 = last.1.max(t2),
+            _ => merged.push((t1, t2)),
+        }
+    }
+    merged
+}
+
+/// Remove the `covered` intervals from `intervals`, dropping what is shorter than `tolerance`.
+/// Both arguments must be sorted and non-overlapping.
+fn subtract(intervals: &[(f64, f64)], covered: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    let mut left = vec![];
+    for &(mut t1, t2) in intervals {
+        for &(c1, c2) in covered {
+            if c2 <= t1 || c1 >= t2 {
+                continue;
+            }
+            if c1 - t1 > tolerance {
+                left.push((t1, c1));
+            }
+            t1 = t1.max(c2);
+        }
+        if t2 - t1 > tolerance {
+            left.push((t1, t2));
+        }
+    }
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::optimize::dedup::remove_duplicate_segments;
+    use crate::prelude::*;
+    use crate::traits::ToShape;
+    use crate::Shape;
+    use approx::assert_relative_eq;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn length(elements: &[Shape]) -> f64 {
+        elements
+            .iter()
+            .flat_map(|e| e.flatten(0.01))
+            .map(|l| {
+                l.points
+                    .windows(2)
+                    .map(|p| p[0].distance(p[1]))
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        let left = Rect::new(Vec2::new(0., 0.), 10., 10.).to_shape();
+        let right = Rect::new(Vec2::new(10., 0.), 10., 10.).to_shape();
+        let layers = remove_duplicate_segments(vec![vec![left, right]], 0.001);
+        assert_relative_eq!(length(&layers[0]), 70., epsilon = 0.0001);
+    }
+
+    #[test]
+    fn overlapping_segments_are_merged() {
+        let elements = vec![
+            LineString::line(Vec2::new(0., 0.), Vec2::new(10., 10.)).to_shape(),
+            LineString::line(Vec2::new(15., 15.), Vec2::new(5., 5.0001)).to_shape(),
+        ];
+        let layers = remove_duplicate_segments(vec![elements], 0.001);
+        assert_eq!(layers[0].len(), 1);
+        assert_relative_eq!(length(&layers[0]), 15. * 2_f64.sqrt(), epsilon = 0.001);
+    }
+
+    #[test]
+    fn segments_are_kept_in_the_first_layer() {
+        let first = vec![LineString::line(Vec2::new(0., 5.), Vec2::new(10., 5.)).to_shape()];
+        let second = vec![
+            LineString::line(Vec2::new(5., 5.), Vec2::new(20., 5.)).to_shape(),
+            Circle::new(Vec2::new(3., 3.), 1.).to_shape(),
+            Circle::new(Vec2::new(3., 3.), 1.).to_shape(),
+        ];
+        let layers = remove_duplicate_segments(vec![first, second], 0.001);
+        assert_relative_eq!(length(&layers[0]), 10., epsilon = 0.0001);
+        assert_eq!(layers[1].len(), 2);
+        assert_eq!(layers[1][0].path_start(), Vec2::new(10., 5.));
+    }
+
+    #[test]
+    fn distinct_segments_keep_their_length() {
+        // Many segments with close directions, none of them on the same line
+        let mut rng = StdRng::seed_from_u64(42);
+        let elements = (0..20000)
+            .map(|_| {
+                let a = Vec2::new(rng.gen_range(0.0..100.), rng.gen_range(0.0..100.));
+                let angle = Angle::from_radians(rng.gen_range(0.0..PI));
+                let b = a + Vec2::from_polar(angle, rng.gen_range(1.0..8.));
+                LineString::line(a, b).to_shape()
+            })
+            .collect::<Vec<Shape>>();
+        let before = length(&elements);
+        let layers = remove_duplicate_segments(vec![elements], 0.01);
+        assert_relative_eq!(length(&layers[0]), before, max_relative = 1e-4);
+    }
+}
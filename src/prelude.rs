@@ -2,14 +2,23 @@
 
 pub use crate::angle::Angle;
 pub use crate::clamp;
+pub use crate::contour::Contours;
+pub use crate::flow::Streamlines;
 pub use crate::group::Group;
+pub use crate::import::import_svg;
 pub use crate::layout::Orientation::{Landscape, Portrait};
 pub use crate::layout::PageLayout;
 pub use crate::map_range;
+pub use crate::occlusion::{occlude, Occluder};
 pub use crate::pen::Pen;
+pub use crate::render::GcodeConfig;
+pub use crate::ridgeline::Ridgelines;
 pub use crate::seed::Seed;
+pub use crate::shapes::arc::{Arc, Direction};
+pub use crate::shapes::bezier::Bezier;
 pub use crate::shapes::circle::Circle;
 pub use crate::shapes::edge::Edge;
+pub use crate::shapes::ellipse::Ellipse;
 pub use crate::shapes::hexagon::Hexagon;
 pub use crate::shapes::linestring::LineString;
 pub use crate::shapes::polygon::Polygon;
@@ -17,13 +26,19 @@ pub use crate::shapes::rectangle::Rect;
 pub use crate::shapes::triangle::Triangle;
 pub use crate::shapes::Text;
 pub use crate::sketch::Debug;
+pub use crate::sketch::Optimization;
 pub use crate::sketch::Sketch;
+pub use crate::stats::PlotterSpeed;
 pub use crate::style::Style;
+pub use crate::traits::boolean::Boolean;
+pub use crate::traits::hatch::{Brightness, DensityHatch, SpiralHatch};
+pub use crate::traits::offset::{JoinStyle, Offset};
 pub use crate::traits::packing::CirclePacking;
 pub use crate::traits::Centroid;
 pub use crate::traits::Chaikin;
 pub use crate::traits::Clip;
 pub use crate::traits::Contains;
+pub use crate::traits::DensityFill;
 pub use crate::traits::HatchFill;
 pub use crate::traits::Lerp;
 pub use crate::traits::Rotate;
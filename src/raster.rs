@@ -0,0 +1,267 @@
+//! A CPU rasteriser producing previews of sketches
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::sketch::Sketch;
+use crate::uom::Uom;
+use crate::vec2::Vec2;
+use anyhow::{Context, Result};
+
+// Resolution of CSS pixels, the unit of the page layout
+const CSS_DPI: f64 = 96.;
+// Maximum deviation, in output pixels, when approximating curves
+const TOLERANCE: f64 = 0.25;
+
+/// An RGB color with an alpha channel, all components in the `[0, 1]` range.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rgba {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
+impl Rgba {
+    pub fn new(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Parse a CSS color: a named color, `#rgb`, `#rrggbb`, `rgb(...)` or `rgba(...)`.
+    pub fn parse(css: &str) -> Option<Self> {
+        let css = css.trim().to_lowercase();
+        if let Some(hex) = css.strip_prefix('#') {
+            let digits = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as f64))
+                .collect::<Option<Vec<f64>>>()?;
+            return match digits.len() {
+                3 => Some(Self::new(
+                    digits[0] / 15.,
+                    digits[1] / 15.,
+                    digits[2] / 15.,
+                    1.,
+                )),
+                6 => Some(Self::new(
+                    (digits[0] * 16. + digits[1]) / 255.,
+                    (digits[2] * 16. + digits[3]) / 255.,
+                    (digits[4] * 16. + digits[5]) / 255.,
+                    1.,
+                )),
+                _ => None,
+            };
+        }
+        if let Some(args) = css
+            .strip_prefix("rgba(")
+            .or_else(|| css.strip_prefix("rgb("))
+        {
+            let values = args
+                .strip_suffix(')')?
+                .split(',')
+                .map(|v| v.trim().parse::<f64>().ok())
+                .collect::<Option<Vec<f64>>>()?;
+            return match values.len() {
+                3 => Some(Self::new(
+                    values[0] / 255.,
+                    values[1] / 255.,
+                    values[2] / 255.,
+                    1.,
+                )),
+                4 => Some(Self::new(
+                    values[0] / 255.,
+                    values[1] / 255.,
+                    values[2] / 255.,
+                    values[3],
+                )),
+                _ => None,
+            };
+        }
+        let (r, g, b) = match css.as_str() {
+            "black" => (0, 0, 0),
+            "white" => (255, 255, 255),
+            "red" => (255, 0, 0),
+            "green" => (0, 128, 0),
+            "blue" => (0, 0, 255),
+            "yellow" => (255, 255, 0),
+            "orange" => (255, 165, 0),
+            "gold" => (255, 215, 0),
+            "purple" => (128, 0, 128),
+            "pink" => (255, 192, 203),
+            "brown" => (165, 42, 42),
+            "cyan" => (0, 255, 255),
+            "magenta" => (255, 0, 255),
+            "gray" | "grey" => (128, 128, 128),
+            "silver" => (192, 192, 192),
+            "navy" => (0, 0, 128),
+            "teal" => (0, 128, 128),
+            "none" | "transparent" => return Some(Self::new(0., 0., 0., 0.)),
+            _ => return None,
+        };
+        Some(Self::new(
+            r as f64 / 255.,
+            g as f64 / 255.,
+            b as f64 / 255.,
+            1.,
+        ))
+    }
+}
+
+/// An RGB image.
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgba>,
+}
+
+impl Raster {
+    /// Create a new image filled with `background`.
+    pub fn new(width: usize, height: usize, background: Rgba) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Return the color of the pixel at column `x` and row `y`.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgba {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Draw `paths` as a single stroke of `color` and `width`, in pixels.
+    ///
+    /// Overlapping segments of the same stroke don't darken each other,
+    /// as it happens with a real pen passing twice on the same line.
+    pub fn stroke(&mut self, paths: &[Vec<Vec2>], color: Rgba, width: f64) {
+        let half = f64::max(width, 1.) / 2.;
+        let mut coverage = vec![0_f32; self.width * self.height];
+        for path in paths {
+            let segments = if path.len() == 1 {
+                vec![(path[0], path[0])]
+            } else {
+                path.windows(2).map(|p| (p[0], p[1])).collect()
+            };
+            for (a, b) in segments {
+                let xmin = (a.x.min(b.x) - half - 1.).floor().max(0.) as usize;
+                let ymin = (a.y.min(b.y) - half - 1.).floor().max(0.) as usize;
+                let xmax = ((a.x.max(b.x) + half + 1.).ceil().max(0.) as usize).min(self.width);
+                let ymax = ((a.y.max(b.y) + half + 1.).ceil().max(0.) as usize).min(self.height);
+                for y in ymin..ymax {
+                    for x in xmin..xmax {
+                        let p = Vec2::new(x as f64 + 0.5, y as f64 + 0.5);
+                        let c = (half + 0.5 - p.distance_to_segment(a, b)).clamp(0., 1.) as f32;
+                        let cell = &mut coverage[y * self.width + x];
+                        *cell = cell.max(c);
+                    }
+                }
+            }
+        }
+        for (pixel, c) in self.pixels.iter_mut().zip(coverage) {
+            if c > 0. {
+                let alpha = c as f64 * color.a;
+                pixel.r = pixel.r * (1. - alpha) + color.r * alpha;
+                pixel.g = pixel.g * (1. - alpha) + color.g * alpha;
+                pixel.b = pixel.b * (1. - alpha) + color.b * alpha;
+            }
+        }
+    }
+
+    /// Save the image as a PNG file.
+    pub fn save_png(&self, path: &str) -> Result<()> {
+        let file = File::create(path).context("Cannot create PNG file")?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data = self
+            .pixels
+            .iter()
+            .flat_map(|p| [p.r, p.g, p.b])
+            .map(|c| (c.clamp(0., 1.) * 255.).round() as u8)
+            .collect::<Vec<u8>>();
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .context("Cannot write PNG file")?;
+        Ok(())
+    }
+}
+
+/// Rasterise `sketch` at `dpi` dots per inch.
+///
+/// Every group is drawn with the color and width of its `Style`, on a white
+/// page unless the page layout style sets a `background` color.
+pub fn render_raster(sketch: &Sketch, dpi: f64) -> Raster {
+    let scale = dpi / CSS_DPI;
+    let width = (sketch.layout.width * scale).round() as usize;
+    let height = (sketch.layout.height * scale).round() as usize;
+    let background = sketch
+        .layout
+        .style
+        .as_ref()
+        .and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (property, value) = declaration.split_once(':')?;
+                match property.trim() {
+                    "background" | "background-color" => Rgba::parse(value),
+                    _ => None,
+                }
+            })
+        })
+        .unwrap_or(Rgba::new(1., 1., 1., 1.));
+
+    let mut raster = Raster::new(width, height, background);
+    let tolerance = Uom::convert_scalar(TOLERANCE / scale, Uom::Px, sketch.uom);
+    for group in &sketch.groups {
+        let color = Rgba::parse(&group.style.stroke).unwrap_or(Rgba::new(0., 0., 0., 1.));
+        let stroke_width = Uom::parse_css_length(&group.style.stroke_width).unwrap_or(1.) * scale;
+        let paths = group
+            .elements
+            .iter()
+            .flat_map(|e| e.flatten(tolerance))
+            .map(|l| {
+                l.points
+                    .iter()
+                    .map(|p| Uom::convert_vec2(*p, sketch.uom, Uom::Px) * scale)
+                    .collect::<Vec<Vec2>>()
+            })
+            .filter(|points| !points.is_empty())
+            .collect::<Vec<Vec<Vec2>>>();
+        raster.stroke(&paths, color, stroke_width);
+    }
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::raster::{render_raster, Rgba};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("black", Some(Rgba::new(0., 0., 0., 1.)))]
+    #[case("White", Some(Rgba::new(1., 1., 1., 1.)))]
+    #[case("#f00", Some(Rgba::new(1., 0., 0., 1.)))]
+    #[case("#0000ff", Some(Rgba::new(0., 0., 1., 1.)))]
+    #[case("rgb(255, 0, 0)", Some(Rgba::new(1., 0., 0., 1.)))]
+    #[case("rgba(0, 0, 255, 0.5)", Some(Rgba::new(0., 0., 1., 0.5)))]
+    #[case("not a color", None)]
+    fn parse_color(#[case] css: &str, #[case] expected: Option<Rgba>) {
+        assert_eq!(Rgba::parse(css), expected);
+    }
+
+    #[test]
+    fn draws_strokes() {
+        let mut sketch = Sketch::new(&PageLayout::axidraw_minikit(Portrait), Uom::Px, Debug::Off);
+        sketch
+            .group(0)
+            .add(LineString::line(Vec2::new(10., 50.), Vec2::new(100., 50.)));
+        sketch.group(0).set_style(Style::new("red", "4px"));
+        let raster = render_raster(&sketch, 96.);
+        assert_eq!(raster.width, 384);
+        assert_eq!(raster.height, 576);
+        assert_eq!(raster.pixel(50, 50), Rgba::new(1., 0., 0., 1.));
+        assert_eq!(raster.pixel(50, 60), Rgba::new(1., 1., 1., 1.));
+        assert_eq!(raster.pixel(5, 50), Rgba::new(1., 1., 1., 1.));
+    }
+}
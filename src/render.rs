@@ -1,14 +1,16 @@
+use crate::angle::Angle;
+use crate::group::Group;
+use crate::shapes::bezier::Curve;
 use crate::sketch::Sketch;
 use crate::uom::Uom;
 use crate::vec2::Vec2;
 use crate::Shape;
-use std::time::Instant;
+use std::f64::consts::PI;
+use std::fmt::Write;
 use svg::node::element::path::Data;
 use svg::Document;
 
 pub fn render_svg(sketch: &Sketch) -> Document {
-    let start = Instant::now();
-
     let mut doc = Document::new()
         .set(
             "xmlns:inkscape",
@@ -31,13 +33,46 @@ pub fn render_svg(sketch: &Sketch) -> Document {
         group = group.set("stroke", l.style.stroke.clone());
         group = group.set("stroke-width", l.style.stroke_width.clone());
 
-        // Optimization
-        // Very naive, very slow, could not optimize for a lot of cases (makes bold assumptions)
-        let mut elements = l.elements.clone();
-        elements.sort_by_key(|e| (e.path_start().x as u64 / 10, e.path_start().y as u64));
-
-        for e in elements.iter() {
+        for e in l.elements.iter() {
             match e {
+                Shape::Arc(s) => {
+                    let px = |p: Vec2| Uom::convert_vec2(p, sketch.uom, Uom::Px);
+                    let radius = Uom::convert_scalar(s.radius, sketch.uom, Uom::Px);
+                    let sweep = s.sweep();
+                    let flag = if sweep > 0. { 1 } else { 0 };
+                    let start = px(s.start_point());
+                    let mut data = Data::new().move_to((start.x, start.y));
+                    // Split arcs longer than half a turn, so that they never
+                    // need the large arc flag, and full circles can be drawn
+                    if sweep.abs() > PI {
+                        let mid = px(s.point_at(s.start + Angle::from_radians(sweep / 2.)));
+                        data = data.elliptical_arc_to((radius, radius, 0, 0, flag, mid.x, mid.y));
+                    }
+                    let end = px(s.end_point());
+                    data = data.elliptical_arc_to((radius, radius, 0, 0, flag, end.x, end.y));
+                    let e = svg::node::element::Path::new().set("d", data);
+                    group = group.add(e);
+                }
+                Shape::Bezier(s) => {
+                    let px = |p: Vec2| {
+                        let p = Uom::convert_vec2(p, sketch.uom, Uom::Px);
+                        (p.x, p.y)
+                    };
+                    let mut data = Data::new();
+                    for (i, curve) in s.curves().iter().enumerate() {
+                        if i == 0 || s.curves()[i - 1].end() != curve.start() {
+                            data = data.move_to(px(curve.start()));
+                        }
+                        data = match *curve {
+                            Curve::Quadratic(_, c, p1) => data.quadratic_curve_to((px(c), px(p1))),
+                            Curve::Cubic(_, c1, c2, p1) => {
+                                data.cubic_curve_to((px(c1), px(c2), px(p1)))
+                            }
+                        };
+                    }
+                    let e = svg::node::element::Path::new().set("d", data);
+                    group = group.add(e);
+                }
                 Shape::Circle(s) => {
                     let center_uom = Uom::convert_vec2(s.center, sketch.uom, Uom::Px);
                     let radius_uom = Uom::convert_scalar(s.radius, sketch.uom, Uom::Px);
@@ -47,6 +82,26 @@ pub fn render_svg(sketch: &Sketch) -> Document {
                         .set("r", radius_uom);
                     group = group.add(e);
                 }
+                Shape::Ellipse(s) => {
+                    let center_uom = Uom::convert_vec2(s.center, sketch.uom, Uom::Px);
+                    let mut e = svg::node::element::Ellipse::new()
+                        .set("cx", center_uom.x)
+                        .set("cy", center_uom.y)
+                        .set("rx", Uom::convert_scalar(s.rx, sketch.uom, Uom::Px))
+                        .set("ry", Uom::convert_scalar(s.ry, sketch.uom, Uom::Px));
+                    if s.rotation.radians != 0. {
+                        e = e.set(
+                            "transform",
+                            format!(
+                                "rotate({} {} {})",
+                                s.rotation.to_degrees(),
+                                center_uom.x,
+                                center_uom.y
+                            ),
+                        );
+                    }
+                    group = group.add(e);
+                }
                 Shape::Rectangle(s) => {
                     let xy_uom = Uom::convert_vec2(s.xy, sketch.uom, Uom::Px);
                     let width_uom = Uom::convert_scalar(s.width, sketch.uom, Uom::Px);
@@ -108,17 +163,22 @@ pub fn render_svg(sketch: &Sketch) -> Document {
                 }
                 Shape::Polygon(s) => {
                     // TODO: maybe use polygon instead of path?
-                    let points_uom = s
-                        .points
-                        .iter()
-                        .map(|p| Uom::convert_vec2(*p, sketch.uom, Uom::Px))
-                        .collect::<Vec<Vec2>>();
-                    let mut data = Data::new().move_to((points_uom[0].x, points_uom[0].y));
-                    for p in points_uom[1..].iter() {
-                        data = data.line_to((p.x, p.y));
+                    let mut data = Data::new();
+                    for ring in std::iter::once(&s.points).chain(s.interiors.iter()) {
+                        let points_uom = ring
+                            .iter()
+                            .map(|p| Uom::convert_vec2(*p, sketch.uom, Uom::Px))
+                            .collect::<Vec<Vec2>>();
+                        data = data.move_to((points_uom[0].x, points_uom[0].y));
+                        for p in points_uom[1..].iter() {
+                            data = data.line_to((p.x, p.y));
+                        }
+                        data = data.close();
+                    }
+                    let mut e = svg::node::element::Path::new().set("d", data);
+                    if !s.interiors.is_empty() {
+                        e = e.set("fill-rule", "evenodd");
                     }
-                    data = data.close();
-                    let e = svg::node::element::Path::new().set("d", data);
                     group = group.add(e);
                 }
                 Shape::Text(s) => {
@@ -133,9 +193,434 @@ pub fn render_svg(sketch: &Sketch) -> Document {
         }
         doc = doc.add(group);
     }
-    println!(
-        "Time elapsed inside render(): {} milliseconds",
-        start.elapsed().as_millis()
-    );
     doc
 }
+
+/// Settings for the G-code backend. All lengths are in millimeters.
+#[derive(Clone)]
+pub struct GcodeConfig {
+    /// Command lifting the pen from the paper.
+    pub pen_up: String,
+    /// Command lowering the pen on the paper.
+    pub pen_down: String,
+    /// Seconds to wait after every pen up/down, to let the servo settle.
+    pub pen_delay: f64,
+    /// Feed rate of pen-down moves, in mm/min.
+    pub draw_feed_rate: f64,
+    /// Feed rate of pen-up moves, in mm/min.
+    pub travel_feed_rate: f64,
+    /// Command pausing the job between two groups, so that the pen can be
+    /// swapped. `None` plots all the groups in a row.
+    pub tool_change: Option<String>,
+    /// Maximum deviation allowed when approximating curves with polylines.
+    pub tolerance: f64,
+}
+
+impl GcodeConfig {
+    pub fn new(pen_up: &str, pen_down: &str, draw_feed_rate: f64, travel_feed_rate: f64) -> Self {
+        Self {
+            pen_up: pen_up.to_string(),
+            pen_down: pen_down.to_string(),
+            draw_feed_rate,
+            travel_feed_rate,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for GcodeConfig {
+    /// Settings suitable for a GRBL plotter driving the pen with a servo on the spindle output.
+    fn default() -> Self {
+        Self {
+            pen_up: "M5".to_string(),
+            pen_down: "M3 S1000".to_string(),
+            pen_delay: 0.15,
+            draw_feed_rate: 1500.,
+            travel_feed_rate: 3000.,
+            tool_change: Some("M0".to_string()),
+            tolerance: 0.1,
+        }
+    }
+}
+
+/// Return the polylines a pen would trace to draw `group`, converted from `from` to `to`.
+///
+/// `tolerance` is expressed in `to` units.
+fn plot_paths(group: &Group, from: Uom, to: Uom, tolerance: f64) -> Vec<Vec<Vec2>> {
+    let tolerance = Uom::convert_scalar(tolerance, to, from);
+    group
+        .elements
+        .iter()
+        .flat_map(|e| e.flatten(tolerance))
+        .filter(|l| !l.points.is_empty())
+        .map(|l| {
+            l.points
+                .iter()
+                .map(|p| Uom::convert_vec2(*p, from, to))
+                .collect::<Vec<Vec2>>()
+        })
+        .collect()
+}
+
+/// Render `sketch` as G-code, with coordinates in millimeters.
+///
+/// Coordinates are the same of the SVG output: the origin is the top-left
+/// corner of the page and y grows downwards. Travel moves are emitted as
+/// `G1` rather than `G0` so that `travel_feed_rate` is honoured.
+pub fn render_gcode(sketch: &Sketch, config: &GcodeConfig) -> String {
+    fn pen_command(out: &mut String, command: &str, delay: f64) {
+        writeln!(out, "{command}").unwrap();
+        if delay > 0. {
+            writeln!(out, "G4 P{delay}").unwrap();
+        }
+    }
+    let pen_up = |out: &mut String| pen_command(out, &config.pen_up, config.pen_delay);
+    let pen_down = |out: &mut String| pen_command(out, &config.pen_down, config.pen_delay);
+
+    let mut out = String::new();
+    writeln!(out, "G21").unwrap();
+    writeln!(out, "G90").unwrap();
+    pen_up(&mut out);
+
+    let mut first = true;
+    for (id, group) in sketch.groups.iter().enumerate() {
+        let paths = plot_paths(group, sketch.uom, Uom::Mm, config.tolerance);
+        if paths.is_empty() {
+            continue;
+        }
+        writeln!(out, "; Group {}", id + 1).unwrap();
+        if !first {
+            if let Some(tool_change) = &config.tool_change {
+                writeln!(out, "{tool_change}").unwrap();
+            }
+        }
+        first = false;
+
+        let mut position: Option<Vec2> = None;
+        for path in paths {
+            let start = path[0];
+            let contiguous = position.is_some_and(|p| p.distance(start) < 1e-6);
+            if !contiguous {
+                if position.is_some() {
+                    pen_up(&mut out);
+                }
+                writeln!(
+                    out,
+                    "G1 X{:.3} Y{:.3} F{}",
+                    start.x, start.y, config.travel_feed_rate
+                )
+                .unwrap();
+                pen_down(&mut out);
+            }
+            for p in path[1..].iter() {
+                writeln!(out, "G1 X{:.3} Y{:.3} F{}", p.x, p.y, config.draw_feed_rate).unwrap();
+            }
+            position = path.last().copied();
+        }
+        pen_up(&mut out);
+    }
+
+    writeln!(out, "G1 X0 Y0 F{}", config.travel_feed_rate).unwrap();
+    out
+}
+
+// HPGL plotter units per millimeter (one unit is 0.025 mm)
+const HPGL_UNITS_PER_MM: f64 = 40.;
+// Maximum deviation, in millimeters, when approximating curves for HPGL output
+const HPGL_TOLERANCE: f64 = 0.1;
+
+/// Render `sketch` as HPGL, selecting pen `n` for the `n`-th group.
+///
+/// Coordinates are in plotter units and, as HPGL expects, the origin is
+/// the bottom-left corner of the page with y growing upwards.
+pub fn render_hpgl(sketch: &Sketch) -> String {
+    let height = Uom::convert_scalar(sketch.height(), sketch.uom, Uom::Mm);
+    let to_units = |p: &Vec2| {
+        (
+            (p.x * HPGL_UNITS_PER_MM).round() as i64,
+            ((height - p.y) * HPGL_UNITS_PER_MM).round() as i64,
+        )
+    };
+
+    let mut out = String::new();
+    writeln!(out, "IN;").unwrap();
+    for (id, group) in sketch.groups.iter().enumerate() {
+        let paths = plot_paths(group, sketch.uom, Uom::Mm, HPGL_TOLERANCE);
+        if paths.is_empty() {
+            continue;
+        }
+        writeln!(out, "SP{};", id + 1).unwrap();
+        for path in paths {
+            let (x, y) = to_units(&path[0]);
+            writeln!(out, "PU{x},{y};").unwrap();
+            if path.len() > 1 {
+                let coords = path[1..]
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = to_units(p);
+                        format!("{x},{y}")
+                    })
+                    .collect::<Vec<String>>()
+                    .join(",");
+                writeln!(out, "PD{coords};").unwrap();
+            }
+        }
+        writeln!(out, "PU;").unwrap();
+    }
+    writeln!(out, "SP0;").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::render::{render_gcode, render_hpgl, render_svg, GcodeConfig};
+    use crate::traits::ToShape;
+    use crate::Shape;
+
+    fn sketch() -> Sketch {
+        Sketch::new(&PageLayout::a6(Portrait), Uom::Mm, Debug::Off)
+    }
+
+    #[test]
+    fn svg_bezier_curve_commands() {
+        let mut sketch = Sketch::new(&PageLayout::a6(Portrait), Uom::Px, Debug::Off);
+        sketch.group(0).add(
+            Bezier::quadratic(Vec2::new(0., 0.), Vec2::new(5., 10.), Vec2::new(10., 0.)).cubic_to(
+                Vec2::new(15., -10.),
+                Vec2::new(20., 10.),
+                Vec2::new(25., 0.),
+            ),
+        );
+        let svg = render_svg(&sketch).to_string();
+        assert!(svg.contains(r#"d="M0,0 Q5,10,10,0 C15,-10,20,10,25,0""#));
+    }
+
+    #[test]
+    fn svg_arcs_and_ellipses() {
+        let mut sketch = Sketch::new(&PageLayout::a6(Portrait), Uom::Px, Debug::Off);
+        sketch.group(0).add(Arc::new(
+            Vec2::new(10., 10.),
+            5.,
+            Angle::zero(),
+            Angle::from_degrees(90.),
+            Direction::Clockwise,
+        ));
+        sketch.group(0).add(Arc::new(
+            Vec2::new(10., 10.),
+            5.,
+            Angle::zero(),
+            Angle::from_degrees(90.),
+            Direction::CounterClockwise,
+        ));
+        sketch.group(0).add(Ellipse::new(
+            Vec2::new(10., 10.),
+            5.,
+            2.,
+            Angle::from_degrees(45.),
+        ));
+        let svg = render_svg(&sketch).to_string();
+        assert!(svg.contains(r#"d="M15,10 A5,5,0,0,1,10,15""#));
+        assert!(svg.contains(r#"d="M15,10 A5,5,0,0,0,6.464466,6.464466 A5,5,0,0,0,10,15""#));
+        assert!(svg
+            .contains(r#"<ellipse cx="10" cy="10" rx="5" ry="2" transform="rotate(45 10 10)"/>"#));
+    }
+
+    #[test]
+    fn gcode_line() {
+        let mut sketch = sketch();
+        sketch
+            .group(0)
+            .add(LineString::line(Vec2::new(1., 2.), Vec2::new(3., 4.)));
+        let mut config = GcodeConfig::new("PEN UP", "PEN DOWN", 1000., 2000.);
+        config.pen_delay = 0.;
+        let gcode = render_gcode(&sketch, &config);
+        assert_eq!(
+            gcode,
+            "G21\nG90\nPEN UP\n; Group 1\nG1 X1.000 Y2.000 F2000\nPEN DOWN\n\
+             G1 X3.000 Y4.000 F1000\nPEN UP\nG1 X0 Y0 F2000\n"
+        );
+    }
+
+    #[test]
+    fn gcode_contiguous_paths_keep_pen_down() {
+        let mut sketch = sketch();
+        sketch
+            .group(0)
+            .add(LineString::line(Vec2::new(1., 2.), Vec2::new(3., 4.)));
+        sketch
+            .group(0)
+            .add(LineString::line(Vec2::new(3., 4.), Vec2::new(5., 6.)));
+        let gcode = render_gcode(&sketch, &GcodeConfig::default());
+        assert_eq!(gcode.matches("M3 S1000").count(), 1);
+    }
+
+    #[test]
+    fn gcode_tool_change_between_groups() {
+        let mut sketch = sketch();
+        sketch.group(0).add(Circle::new(Vec2::new(10., 10.), 5.));
+        sketch.group(3).add(Rect::new(Vec2::new(1., 1.), 2., 2.));
+        sketch
+            .group(5)
+            .add(Text::new(Vec2::new(1., 1.), "not plotted"));
+        let gcode = render_gcode(&sketch, &GcodeConfig::default());
+        assert_eq!(gcode.matches("M0\n").count(), 1);
+        assert!(gcode.contains("; Group 1\n"));
+        assert!(gcode.contains("; Group 4\n"));
+        assert!(!gcode.contains("; Group 6\n"));
+    }
+
+    #[test]
+    fn gcode_converts_to_millimeters() {
+        let mut sketch = Sketch::new(&PageLayout::a6(Portrait), Uom::In, Debug::Off);
+        sketch
+            .group(0)
+            .add(LineString::line(Vec2::new(0., 0.), Vec2::new(1., 2.)));
+        let gcode = render_gcode(&sketch, &GcodeConfig::default());
+        assert!(gcode.contains("G1 X25.400 Y50.800 F1500"));
+    }
+
+    /// Parse the output of `render_hpgl` back into pens and polylines, in millimeters.
+    fn parse_hpgl(hpgl: &str, height: f64) -> Vec<(usize, Vec<Vec2>)> {
+        let mut paths: Vec<(usize, Vec<Vec2>)> = vec![];
+        let mut pen = 0;
+        for instruction in hpgl.split(';').map(|i| i.trim()) {
+            let (mnemonic, args) = instruction.split_at(instruction.len().min(2));
+            if mnemonic == "SP" {
+                pen = args.parse().unwrap();
+                continue;
+            }
+            let coords = args
+                .split(',')
+                .filter(|a| !a.is_empty())
+                .map(|a| a.parse::<f64>().unwrap() / 40.)
+                .collect::<Vec<f64>>();
+            let points = coords
+                .chunks(2)
+                .map(|c| Vec2::new(c[0], height - c[1]))
+                .collect::<Vec<Vec2>>();
+            match mnemonic {
+                "PU" if !points.is_empty() => paths.push((pen, points)),
+                "PD" => paths.last_mut().unwrap().1.extend(points),
+                _ => {}
+            }
+        }
+        paths
+    }
+
+    #[test]
+    fn hpgl_round_trip() {
+        let mut sketch = sketch();
+        let shapes = [
+            LineString::new(vec![
+                Vec2::new(10., 10.),
+                Vec2::new(20., 15.),
+                Vec2::new(30., 12.5),
+            ])
+            .to_shape(),
+            Rect::new(Vec2::new(5., 5.), 50., 20.).to_shape(),
+            Hexagon::new(Vec2::new(60., 60.), 10., Angle::zero()).to_shape(),
+            Triangle::new(Vec2::new(1., 1.), Vec2::new(9., 1.), Vec2::new(5., 7.)).to_shape(),
+            Circle::new(Vec2::new(40., 80.), 12.).to_shape(),
+        ];
+        for (i, shape) in shapes.iter().enumerate() {
+            sketch.group(i % 2).elements.push(shape.clone());
+        }
+        let parsed = parse_hpgl(&render_hpgl(&sketch), sketch.height());
+        let expected = sketch
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(id, g)| {
+                g.elements
+                    .iter()
+                    .flat_map(|e: &Shape| e.flatten(0.1))
+                    .map(move |l| (id + 1, l.points))
+            })
+            .collect::<Vec<(usize, Vec<Vec2>)>>();
+        assert_eq!(parsed.len(), expected.len());
+        for ((pen, points), (expected_pen, expected_points)) in parsed.iter().zip(expected.iter()) {
+            assert_eq!(pen, expected_pen);
+            assert_eq!(points.len(), expected_points.len());
+            for (p, e) in points.iter().zip(expected_points.iter()) {
+                assert!(p.distance(*e) <= 0.025);
+            }
+        }
+    }
+
+    #[test]
+    fn hpgl_renders_the_cercle_carre_sketch() {
+        // The rotating square spirals of the cercle_carre sample, in pixels
+        let mut sketch = Sketch::new(&PageLayout::axidraw_minikit(Portrait), Uom::Px, Debug::Off);
+        let center = sketch.as_rect().centroid();
+        let side = sketch.as_rect().scale_perc(0.80).min_len();
+        for (id, start_angle) in [TAU * 0.25, TAU * 0.25 + 0.05].into_iter().enumerate() {
+            let rect = Rect::square_with_center(center, side);
+            for factor in 0..60 {
+                let scale = if factor > 0 { factor as f64 / 60. } else { 1.0 };
+                let rect = rect
+                    .scale_perc(scale)
+                    .to_polygon()
+                    .rotate(Angle::from_radians(start_angle + TAU * scale))
+                    .upsample(1)
+                    .chaikin(5, true);
+                sketch.group(id).add(rect);
+            }
+        }
+        let hpgl = render_hpgl(&sketch);
+
+        // Each pen draws paths made of a pen up move, then pen down moves,
+        // and lifts the pen when done
+        let instructions = hpgl.lines().collect::<Vec<&str>>();
+        assert_eq!(instructions[0], "IN;");
+        assert_eq!(instructions[instructions.len() - 1], "SP0;");
+        let pens = hpgl.split("SP").skip(1).collect::<Vec<&str>>();
+        assert_eq!(pens.len(), 3);
+        for (id, pen) in pens[..2].iter().enumerate() {
+            let lines = pen.lines().collect::<Vec<&str>>();
+            assert_eq!(lines[0], format!("{};", id + 1));
+            assert_eq!(lines[lines.len() - 1], "PU;");
+            let moves = &lines[1..lines.len() - 1];
+            assert_eq!(moves.len(), 120);
+            for pair in moves.chunks(2) {
+                assert!(pair[0].starts_with("PU") && pair[1].starts_with("PD"));
+                assert!(pair
+                    .iter()
+                    .flat_map(|m| m[2..m.len() - 1].split(','))
+                    .all(|c| c.parse::<i64>().is_ok()));
+            }
+        }
+
+        // One unit is 0.025 mm
+        let height = Uom::convert_scalar(sketch.height(), Uom::Px, Uom::Mm);
+        let parsed = parse_hpgl(&hpgl, height);
+        let tolerance = Uom::convert_scalar(0.1, Uom::Mm, Uom::Px);
+        let expected = sketch.groups[..2]
+            .iter()
+            .flat_map(|g| g.elements.iter().flat_map(|e| e.flatten(tolerance)))
+            .collect::<Vec<LineString>>();
+        assert_eq!(parsed.len(), expected.len());
+        for ((_, points), line) in parsed.iter().zip(expected.iter()) {
+            assert_eq!(points.len(), line.points.len());
+            for (p, e) in points.iter().zip(line.points.iter()) {
+                let e = Uom::convert_vec2(*e, Uom::Px, Uom::Mm);
+                assert!((p.x - e.x).abs() <= 0.0125 + 1e-9);
+                assert!((p.y - e.y).abs() <= 0.0125 + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn hpgl_page_origin_is_bottom_left() {
+        let mut sketch = sketch();
+        let height = sketch.height();
+        sketch.group(2).add(LineString::line(
+            Vec2::new(0., height),
+            Vec2::new(1., height - 1.),
+        ));
+        assert_eq!(
+            render_hpgl(&sketch),
+            "IN;\nSP3;\nPU0,0;\nPD40,40;\nPU;\nSP0;\n"
+        );
+    }
+}
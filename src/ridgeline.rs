@@ -0,0 +1,160 @@
+//! Stacked horizon lines, in the style of the Joy Division album cover
+
+use crate::field::Scalar2;
+use crate::occlusion::{occlude, Occluder};
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::shapes::rectangle::Rect;
+use crate::vec2::Vec2;
+
+/// The settings of rows of lines raised by a field, each one hiding the
+/// parts of the rows behind it that it passes in front of.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ridgelines {
+    /// The area the lines are spread over and clipped to
+    pub bounds: Rect,
+    /// The number of rows, each one at the middle of its share of the height
+    pub rows: usize,
+    /// The horizontal distance between the points of a row
+    pub spacing: f64,
+    /// The height of a row where the field is 1
+    pub amplitude: f64,
+    /// The factor applied to positions before sampling the field
+    pub frequency: f64,
+}
+
+impl Ridgelines {
+    pub fn new(bounds: Rect, rows: usize) -> Self {
+        Self {
+            rows,
+            spacing: bounds.width / 100.,
+            amplitude: bounds.height / rows as f64,
+            frequency: 1.,
+            bounds,
+        }
+    }
+
+    /// Set the horizontal distance between the points of a row.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Set the height of a row where the field is 1.
+    pub fn with_amplitude(mut self, amplitude: f64) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Set the factor applied to positions before sampling the field.
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Return the rows raised by `field`, from the top to the bottom, with
+    /// the parts hidden by the rows in front of them removed.
+    pub fn lines<F: Scalar2>(&self, field: &F) -> Vec<LineString> {
+        if self.rows == 0 || self.spacing <= 0. {
+            return vec![];
+        }
+        let Rect { xy, width, height } = self.bounds;
+        let row_height = height / self.rows as f64;
+        let columns = (width / self.spacing).ceil().max(1.) as usize;
+        let rows = (0..self.rows)
+            .map(|row| {
+                let baseline = xy.y + (row as f64 + 0.5) * row_height;
+                LineString::new(
+                    (0..=columns)
+                        .map(|column| {
+                            let x = xy.x + width * column as f64 / columns as f64;
+                            let value = field.number2(Vec2::new(x, baseline) * self.frequency);
+                            Vec2::new(x, baseline - value * self.amplitude)
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<Vec<LineString>>();
+
+        // Each row hides everything below it, down past the lowest point
+        let bottom = rows
+            .iter()
+            .flat_map(|r| r.points.iter())
+            .fold(xy.y + height, |bottom, p| bottom.max(p.y))
+            + 1.;
+        let occluders = rows
+            .iter()
+            .rev()
+            .map(|row| {
+                let (first, last) = (row.points[0], row.points[row.points.len() - 1]);
+                let mut mask = row.points.clone();
+                mask.push(Vec2::new(last.x, bottom));
+                mask.push(Vec2::new(first.x, bottom));
+                Occluder::new(Polygon::new(mask), vec![row.clone()])
+            })
+            .collect::<Vec<Occluder>>();
+        occlude(&occluders)
+            .into_iter()
+            .rev()
+            .flatten()
+            .flat_map(|l| l.clip(&self.bounds, false))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angle::Angle;
+    use crate::field::{PerlinField, Scalar2};
+    use crate::prelude::*;
+    use crate::ridgeline::Ridgelines;
+    use approx::assert_relative_eq;
+
+    // A bump in the middle of the x axis, near the bottom only
+    struct Bump;
+
+    impl Scalar2 for Bump {
+        fn number2(&self, pos: Vec2) -> f64 {
+            if pos.y < 80. {
+                return 0.;
+            }
+            (1. - (pos.x - 50.).abs() / 10.).max(0.)
+        }
+
+        fn angle2(&self, _pos: Vec2, _increments: f64) -> Angle {
+            Angle::zero()
+        }
+    }
+
+    #[test]
+    fn flat_rows_are_evenly_spaced() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        // Perlin noise is 0 on integer coordinates
+        let lines = Ridgelines::new(bounds, 4)
+            .with_spacing(1.)
+            .with_frequency(2.)
+            .lines(&PerlinField::new(1));
+        assert_eq!(lines.len(), 4);
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line.points.len(), 101);
+            assert!(line.points.iter().all(|p| p.y == 12.5 + 25. * i as f64));
+        }
+    }
+
+    #[test]
+    fn bumps_hide_the_rows_behind() {
+        let bounds = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let lines = Ridgelines::new(bounds, 4)
+            .with_spacing(1.)
+            .with_amplitude(40.)
+            .lines(&Bump);
+        // The bottom row rises to 47.5, hiding the middle of the row above
+        assert_eq!(lines.len(), 5);
+        let peak = lines[4].points.iter().map(|p| p.y).fold(f64::MAX, f64::min);
+        assert_relative_eq!(peak, 47.5);
+        assert!(lines[2..4]
+            .iter()
+            .flat_map(|l| l.points.iter())
+            .all(|p| p.y == 62.5 && (p.x - 50.).abs() >= 3.75 - 1e-9));
+    }
+}
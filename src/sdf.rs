@@ -0,0 +1,402 @@
+//! Signed distance fields: shapes as fields of the distance to their outline,
+//! negative inside, and the operators combining them
+
+use crate::angle::Angle;
+use crate::field::{value_to_angle, Scalar2};
+use crate::shapes::circle::Circle;
+use crate::shapes::edge::Edge;
+use crate::shapes::hexagon::Hexagon;
+use crate::shapes::polygon::Polygon;
+use crate::shapes::rectangle::Rect;
+use crate::shapes::triangle::Triangle;
+use crate::vec2::Vec2;
+
+impl Scalar2 for Circle {
+    fn number2(&self, pos: Vec2) -> f64 {
+        pos.distance(self.center) - self.radius
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl Scalar2 for Rect {
+    fn number2(&self, pos: Vec2) -> f64 {
+        rounded_box(self, 0., pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl Scalar2 for Hexagon {
+    fn number2(&self, pos: Vec2) -> f64 {
+        even_odd(&[&self.vertexes()], pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+impl Scalar2 for Triangle {
+    fn number2(&self, pos: Vec2) -> f64 {
+        even_odd(&[&[self.a, self.b, self.c]], pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// Holes are outside the polygon, and overlapping ones are combined with the
+/// even-odd rule.
+impl Scalar2 for Polygon {
+    fn number2(&self, pos: Vec2) -> f64 {
+        let rings = std::iter::once(&self.points)
+            .chain(&self.interiors)
+            .map(|r| r.as_slice())
+            .collect::<Vec<&[Vec2]>>();
+        even_odd(&rings, pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// A segment is never negative, having no inside: round it to get a stroke.
+impl Scalar2 for Edge {
+    fn number2(&self, pos: Vec2) -> f64 {
+        pos.distance_to_segment(self.v1, self.v2)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// A rectangle with its corners rounded, within its bounds.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoundedRect {
+    pub rect: Rect,
+    /// The radius of the corners, at most half of the shorter side
+    pub radius: f64,
+}
+
+impl RoundedRect {
+    pub fn new(rect: Rect, radius: f64) -> Self {
+        Self { rect, radius }
+    }
+}
+
+impl Scalar2 for RoundedRect {
+    fn number2(&self, pos: Vec2) -> f64 {
+        let radius = self.radius.clamp(0., self.rect.min_len() / 2.);
+        rounded_box(&self.rect, radius, pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// The union of two fields, blended over `smoothness` where they meet.
+#[derive(Clone)]
+pub struct Union<A, B> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A, B> Union<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            smoothness: 0.,
+        }
+    }
+
+    pub fn with_smoothness(mut self, smoothness: f64) -> Self {
+        self.smoothness = smoothness;
+        self
+    }
+}
+
+impl<A: Scalar2, B: Scalar2> Scalar2 for Union<A, B> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        smooth_min(self.a.number2(pos), self.b.number2(pos), self.smoothness)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// The intersection of two fields, blended over `smoothness` where they meet.
+#[derive(Clone)]
+pub struct Intersection<A, B> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A, B> Intersection<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            smoothness: 0.,
+        }
+    }
+
+    pub fn with_smoothness(mut self, smoothness: f64) -> Self {
+        self.smoothness = smoothness;
+        self
+    }
+}
+
+impl<A: Scalar2, B: Scalar2> Scalar2 for Intersection<A, B> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        -smooth_min(-self.a.number2(pos), -self.b.number2(pos), self.smoothness)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// The field `a` with `b` cut out of it, blended over `smoothness` where
+/// they meet.
+#[derive(Clone)]
+pub struct Subtraction<A, B> {
+    pub a: A,
+    pub b: B,
+    pub smoothness: f64,
+}
+
+impl<A, B> Subtraction<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            smoothness: 0.,
+        }
+    }
+
+    pub fn with_smoothness(mut self, smoothness: f64) -> Self {
+        self.smoothness = smoothness;
+        self
+    }
+}
+
+impl<A: Scalar2, B: Scalar2> Scalar2 for Subtraction<A, B> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        -smooth_min(-self.a.number2(pos), self.b.number2(pos), self.smoothness)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// A field grown by `radius`, rounding its corners.
+#[derive(Clone)]
+pub struct Round<F> {
+    pub field: F,
+    pub radius: f64,
+}
+
+impl<F> Round<F> {
+    pub fn new(field: F, radius: f64) -> Self {
+        Self { field, radius }
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Round<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.field.number2(pos) - self.radius
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// The outline of a field, `thickness` wide and centered on it.
+#[derive(Clone)]
+pub struct Onion<F> {
+    pub field: F,
+    pub thickness: f64,
+}
+
+impl<F> Onion<F> {
+    pub fn new(field: F, thickness: f64) -> Self {
+        Self { field, thickness }
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Onion<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        self.field.number2(pos).abs() - self.thickness / 2.
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// Copies of the cell of a field around the origin, every `period` along
+/// each axis, or not along the axes where the period is 0. The copies are
+/// exact while the shape stays within its cell.
+#[derive(Clone)]
+pub struct Repeat<F> {
+    pub field: F,
+    pub period: Vec2,
+}
+
+impl<F> Repeat<F> {
+    pub fn new(field: F, period: Vec2) -> Self {
+        Self { field, period }
+    }
+}
+
+impl<F: Scalar2> Scalar2 for Repeat<F> {
+    fn number2(&self, pos: Vec2) -> f64 {
+        let wrap = |v: f64, period: f64| {
+            if period == 0. {
+                v
+            } else {
+                v - period * (v / period).round()
+            }
+        };
+        let pos = Vec2::new(wrap(pos.x, self.period.x), wrap(pos.y, self.period.y));
+        self.field.number2(pos)
+    }
+
+    fn angle2(&self, pos: Vec2, increment: f64) -> Angle {
+        value_to_angle(self.number2(pos), increment)
+    }
+}
+
+/// Return the smooth minimum of `a` and `b`, blended over `k`, or the
+/// minimum when `k` is 0.
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0. {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+    b * (1. - h) + a * h - k * h * (1. - h)
+}
+
+/// Return the signed distance to `rect` with its corners rounded by `radius`.
+fn rounded_box(rect: &Rect, radius: f64, pos: Vec2) -> f64 {
+    let half = Vec2::new(rect.width, rect.height) / 2.;
+    let q = (pos - rect.xy - half).abs() - half + radius;
+    let outside = Vec2::new(q.x.max(0.), q.y.max(0.));
+    outside.distance(Vec2::ZERO) + q.x.max(q.y).min(0.) - radius
+}
+
+/// Return the signed distance to the area enclosed by `rings` with the
+/// even-odd rule.
+fn even_odd(rings: &[&[Vec2]], pos: Vec2) -> f64 {
+    let mut distance = f64::MAX;
+    let mut inside = false;
+    for ring in rings {
+        for (i, &a) in ring.iter().enumerate() {
+            let b = ring[(i + 1) % ring.len()];
+            distance = distance.min(pos.distance_to_segment(a, b));
+            if pos.ray_crosses(a, b) {
+                inside = !inside;
+            }
+        }
+    }
+    if inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contour::Contours;
+    use crate::field::Scalar2;
+    use crate::prelude::*;
+    use crate::sdf::{Intersection, Onion, Repeat, Round, RoundedRect, Subtraction, Union};
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    fn square() -> Rect {
+        Rect::new(Vec2::new(0., 0.), 10., 10.)
+    }
+
+    #[rstest]
+    #[case(Vec2::new(5., 5.), -5.)]
+    #[case(Vec2::new(5., 1.), -1.)]
+    #[case(Vec2::new(13., 5.), 3.)]
+    #[case(Vec2::new(13., 14.), 5.)]
+    fn distance_to_a_square(#[case] pos: Vec2, #[case] expected: f64) {
+        assert_relative_eq!(square().number2(pos), expected);
+        let polygon = square().to_polygon();
+        assert_relative_eq!(polygon.number2(pos), expected);
+    }
+
+    #[test]
+    fn distance_to_shapes() {
+        let circle = Circle::new(Vec2::new(0., 0.), 5.);
+        assert_relative_eq!(circle.number2(Vec2::new(3., 4.)), 0.);
+        let hexagon = Hexagon::new(Vec2::new(0., 0.), 2., Angle::zero());
+        assert_relative_eq!(hexagon.number2(Vec2::ZERO), -hexagon.apothem);
+        assert_relative_eq!(hexagon.number2(Vec2::new(3., 0.)), 1.);
+        let triangle = Triangle::new(Vec2::new(0., 0.), Vec2::new(4., 0.), Vec2::new(0., 4.));
+        assert_relative_eq!(triangle.number2(Vec2::new(1., 1.)), -1.);
+        let edge = Edge {
+            v1: Vec2::new(0., 0.),
+            v2: Vec2::new(10., 0.),
+        };
+        assert_relative_eq!(edge.number2(Vec2::new(5., -2.)), 2.);
+        assert_relative_eq!(edge.number2(Vec2::new(13., 4.)), 5.);
+        // The corner is 2 away, past the rounding of radius 1
+        let rounded = RoundedRect::new(square(), 1.);
+        assert_relative_eq!(rounded.number2(Vec2::new(11., 11.)), 2f64.sqrt() * 2. - 1.);
+        // Holes are outside
+        let frame = Polygon::with_interiors(
+            square().to_polygon().points,
+            vec![Rect::new(Vec2::new(2., 2.), 6., 6.).to_polygon().points],
+        );
+        assert_relative_eq!(frame.number2(Vec2::new(5., 5.)), 3.);
+        assert_relative_eq!(frame.number2(Vec2::new(1., 5.)), -1.);
+    }
+
+    #[test]
+    fn operators_combine_distances() {
+        let left = Circle::new(Vec2::new(0., 0.), 2.);
+        let right = Circle::new(Vec2::new(3., 0.), 2.);
+        let p = Vec2::new(1.5, 0.);
+        assert_relative_eq!(Union::new(left, right).number2(p), -0.5);
+        assert_relative_eq!(Intersection::new(left, right).number2(p), -0.5);
+        assert_relative_eq!(Subtraction::new(left, right).number2(p), 0.5);
+        // Smoothing digs into the shapes where they meet
+        let smooth = Union::new(left, right).with_smoothness(1.);
+        assert!(smooth.number2(p) < -0.5);
+        assert_relative_eq!(smooth.number2(Vec2::new(-2., 0.)), 0.);
+        assert_relative_eq!(Round::new(left, 1.).number2(Vec2::new(3., 0.)), 0.);
+        assert_relative_eq!(Onion::new(left, 1.).number2(Vec2::ZERO), 1.5);
+        let dots = Repeat::new(left, Vec2::new(10., 0.));
+        assert_relative_eq!(dots.number2(Vec2::new(31., 0.)), -1.);
+        assert_relative_eq!(dots.number2(Vec2::new(1., 10.)), 10f64.hypot(1.) - 2.);
+    }
+
+    #[test]
+    fn contours_of_distance_fields() {
+        let ring = Onion::new(Circle::new(Vec2::new(50., 50.), 20.), 4.);
+        let bounds = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let isolines = Contours::new(bounds, 1.).isolines(&ring, &[0.]);
+        // The inner and the outer edge of the ring
+        assert_eq!(isolines[0].closed.len(), 2);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod arc;
+pub mod bezier;
 pub mod circle;
 pub mod edge;
+pub mod ellipse;
 pub mod hexagon;
 pub mod linestring;
 pub mod polygon;
@@ -0,0 +1,194 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::angle::Angle;
+use crate::shapes::linestring::LineString;
+use crate::vec2::Vec2;
+
+/// The direction in which an arc is traced, as seen on the page
+/// (where the y axis points downwards).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    /// Towards increasing angles
+    Clockwise,
+    /// Towards decreasing angles
+    CounterClockwise,
+}
+
+/// A circular arc going from the `start` to the `end` angle
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Arc {
+    /// The center point
+    pub center: Vec2,
+    /// The radius
+    pub radius: f64,
+    /// The angle where the arc starts
+    pub start: Angle,
+    /// The angle where the arc ends
+    pub end: Angle,
+    /// The direction from `start` to `end`
+    pub direction: Direction,
+}
+
+impl Arc {
+    pub fn new(center: Vec2, radius: f64, start: Angle, end: Angle, direction: Direction) -> Self {
+        Self {
+            center,
+            radius,
+            start,
+            end,
+            direction,
+        }
+    }
+
+    /// Return the angle, in radians, swept from `start` to `end`: positive
+    /// when clockwise, negative when counterclockwise. Angles one full turn
+    /// apart make a full circle.
+    pub fn sweep(&self) -> f64 {
+        let delta = match self.direction {
+            Direction::Clockwise => self.end.radians - self.start.radians,
+            Direction::CounterClockwise => self.start.radians - self.end.radians,
+        };
+        let mut sweep = delta.rem_euclid(TAU);
+        if sweep == 0. && delta != 0. {
+            sweep = TAU;
+        }
+        match self.direction {
+            Direction::Clockwise => sweep,
+            Direction::CounterClockwise => -sweep,
+        }
+    }
+
+    /// Return the point of the circle at `angle`.
+    pub fn point_at(&self, angle: Angle) -> Vec2 {
+        self.center + Vec2::from_polar(angle, self.radius)
+    }
+
+    pub fn start_point(&self) -> Vec2 {
+        self.point_at(self.start)
+    }
+
+    pub fn end_point(&self) -> Vec2 {
+        self.point_at(self.end)
+    }
+
+    /// Return whether `angle` lies between `start` and `end`.
+    pub fn contains_angle(&self, angle: Angle) -> bool {
+        let sweep = self.sweep();
+        if sweep >= 0. {
+            (angle.radians - self.start.radians).rem_euclid(TAU) <= sweep
+        } else {
+            (self.start.radians - angle.radians).rem_euclid(TAU) <= -sweep
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.sweep().abs() * self.radius
+    }
+
+    /// Return the same arc, traced from the end to the start.
+    pub fn reverse(&self) -> Self {
+        let direction = match self.direction {
+            Direction::Clockwise => Direction::CounterClockwise,
+            Direction::CounterClockwise => Direction::Clockwise,
+        };
+        Self::new(self.center, self.radius, self.end, self.start, direction)
+    }
+
+    /// Approximate the arc with a polyline whose chords deviate from it by at most `tolerance`.
+    pub fn to_linestring(&self, tolerance: f64) -> LineString {
+        let sweep = self.sweep();
+        let n = segments_for_tolerance(self.radius, sweep, tolerance);
+        LineString::new(
+            (0..=n)
+                .map(|i| {
+                    let angle = self.start.radians + sweep * i as f64 / n as f64;
+                    self.point_at(Angle::from_radians(angle))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Return the number of chords needed to approximate an arc of `radius`
+/// spanning `angle` radians, so that no chord deviates from the arc by more
+/// than `tolerance`.
+pub fn segments_for_tolerance(radius: f64, angle: f64, tolerance: f64) -> usize {
+    let step = if tolerance < radius {
+        2. * (1. - tolerance / radius).acos()
+    } else {
+        PI / 2.
+    };
+    usize::max(1, (angle.abs() / step).ceil() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::shapes::arc::Direction::{Clockwise, CounterClockwise};
+    use crate::traits::BoundingBox;
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0., 90., Clockwise, PI / 2.)]
+    #[case(0., 90., CounterClockwise, -3. * PI / 2.)]
+    #[case(350., 10., Clockwise, PI / 9.)]
+    #[case(0., 360., Clockwise, TAU)]
+    #[case(45., 45., Clockwise, 0.)]
+    fn sweep(
+        #[case] start: f64,
+        #[case] end: f64,
+        #[case] direction: Direction,
+        #[case] expected: f64,
+    ) {
+        let arc = Arc::new(
+            Vec2::ZERO,
+            1.,
+            Angle::from_degrees(start),
+            Angle::from_degrees(end),
+            direction,
+        );
+        assert_relative_eq!(arc.sweep(), expected, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn to_linestring_within_tolerance() {
+        let arc = Arc::new(
+            Vec2::new(10., 10.),
+            50.,
+            Angle::from_degrees(300.),
+            Angle::from_degrees(60.),
+            Clockwise,
+        );
+        let line = arc.to_linestring(0.1);
+        assert_eq!(line.points[0], arc.start_point());
+        let last = line.points[line.points.len() - 1];
+        assert_relative_eq!(last.x, arc.end_point().x, epsilon = 1e-9);
+        assert_relative_eq!(last.y, arc.end_point().y, epsilon = 1e-9);
+        for pair in line.points.windows(2) {
+            let mid = (pair[0] + pair[1]) / 2.;
+            assert!(50. - mid.distance(arc.center) <= 0.1);
+            assert!(mid.x > 10.);
+        }
+    }
+
+    #[test]
+    fn bbox_and_centroid() {
+        let arc = Arc::new(
+            Vec2::ZERO,
+            2.,
+            Angle::from_degrees(180.),
+            Angle::zero(),
+            Clockwise,
+        );
+        // The upper half of the circle, as y grows downwards
+        let bbox = arc.bbox();
+        assert_relative_eq!(bbox.xy.x, -2.);
+        assert_relative_eq!(bbox.xy.y, -2.);
+        assert_relative_eq!(bbox.width, 4.);
+        assert_relative_eq!(bbox.height, 2., epsilon = 1e-9);
+        let centroid = arc.centroid();
+        assert_relative_eq!(centroid.x, 0., epsilon = 1e-9);
+        assert_relative_eq!(centroid.y, -4. / PI, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,509 @@
+use crate::shapes::linestring::LineString;
+use crate::traits::Lerp;
+use crate::vec2::Vec2;
+
+// Maximum number of subdivisions when flattening a curve
+const MAX_DEPTH: usize = 16;
+// Intervals used to integrate the length of a curve
+const LENGTH_STEPS: usize = 16;
+// Five-point Gauss-Legendre quadrature: abscissae and weights on [-1, 1]
+const GAUSS_LEGENDRE: [(f64, f64); 5] = [
+    (0., 0.568_888_888_888_888_9),
+    (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+];
+
+/// A single Bézier curve, represented by its control points
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Curve {
+    /// Start, control point and end
+    Quadratic(Vec2, Vec2, Vec2),
+    /// Start, two control points and end
+    Cubic(Vec2, Vec2, Vec2, Vec2),
+}
+
+impl Curve {
+    pub fn start(&self) -> Vec2 {
+        match *self {
+            Curve::Quadratic(p0, _, _) | Curve::Cubic(p0, _, _, _) => p0,
+        }
+    }
+
+    pub fn end(&self) -> Vec2 {
+        match *self {
+            Curve::Quadratic(_, _, p1) | Curve::Cubic(_, _, _, p1) => p1,
+        }
+    }
+
+    /// Return the point of the curve at `t`, in the `[0, 1]` range.
+    pub fn eval(&self, t: f64) -> Vec2 {
+        let u = 1. - t;
+        match *self {
+            Curve::Quadratic(p0, c, p1) => p0 * (u * u) + c * (2. * u * t) + p1 * (t * t),
+            Curve::Cubic(p0, c1, c2, p1) => {
+                p0 * (u * u * u) + c1 * (3. * u * u * t) + c2 * (3. * u * t * t) + p1 * (t * t * t)
+            }
+        }
+    }
+
+    /// Return the first derivative of the curve at `t`, tangent to the curve.
+    pub fn derivative(&self, t: f64) -> Vec2 {
+        let u = 1. - t;
+        match *self {
+            Curve::Quadratic(p0, c, p1) => (c - p0) * (2. * u) + (p1 - c) * (2. * t),
+            Curve::Cubic(p0, c1, c2, p1) => {
+                (c1 - p0) * (3. * u * u) + (c2 - c1) * (6. * u * t) + (p1 - c2) * (3. * t * t)
+            }
+        }
+    }
+
+    /// Split the curve at `t` into two curves of the same degree.
+    pub fn split(&self, t: f64) -> (Curve, Curve) {
+        match *self {
+            Curve::Quadratic(p0, c, p1) => {
+                let (a, b) = (p0.lerp(c, t), c.lerp(p1, t));
+                let mid = a.lerp(b, t);
+                (Curve::Quadratic(p0, a, mid), Curve::Quadratic(mid, b, p1))
+            }
+            Curve::Cubic(p0, c1, c2, p1) => {
+                let (a, b, c) = (p0.lerp(c1, t), c1.lerp(c2, t), c2.lerp(p1, t));
+                let (d, e) = (a.lerp(b, t), b.lerp(c, t));
+                let mid = d.lerp(e, t);
+                (Curve::Cubic(p0, a, d, mid), Curve::Cubic(mid, e, c, p1))
+            }
+        }
+    }
+
+    /// Return the same curve, traced from the end to the start.
+    pub fn reverse(&self) -> Curve {
+        match *self {
+            Curve::Quadratic(p0, c, p1) => Curve::Quadratic(p1, c, p0),
+            Curve::Cubic(p0, c1, c2, p1) => Curve::Cubic(p1, c2, c1, p0),
+        }
+    }
+
+    /// Apply `f` to every control point. Any affine transformation of the
+    /// control points transforms the curve the same way.
+    pub fn map<F: Fn(Vec2) -> Vec2>(&self, f: F) -> Curve {
+        match *self {
+            Curve::Quadratic(p0, c, p1) => Curve::Quadratic(f(p0), f(c), f(p1)),
+            Curve::Cubic(p0, c1, c2, p1) => Curve::Cubic(f(p0), f(c1), f(c2), f(p1)),
+        }
+    }
+
+    /// Return the length of the curve from its start up to `t`.
+    pub fn length_to(&self, t: f64) -> f64 {
+        let step = t / LENGTH_STEPS as f64;
+        (0..LENGTH_STEPS)
+            .map(|i| {
+                let mid = step * (i as f64 + 0.5);
+                GAUSS_LEGENDRE
+                    .iter()
+                    .map(|(x, w)| w * self.derivative(mid + x * step / 2.).distance(Vec2::ZERO))
+                    .sum::<f64>()
+                    * step
+                    / 2.
+            })
+            .sum()
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length_to(1.)
+    }
+
+    /// Return the `t` at which the length of the curve from its start is `distance`.
+    pub fn t_at_length(&self, distance: f64) -> f64 {
+        let length = self.length();
+        if distance <= 0. || length == 0. {
+            return 0.;
+        }
+        if distance >= length {
+            return 1.;
+        }
+        // Newton's method, falling back to bisection when it overshoots
+        let (mut low, mut high) = (0., 1.);
+        let mut t = distance / length;
+        for _ in 0..32 {
+            let error = self.length_to(t) - distance;
+            if error.abs() < length * 1e-12 {
+                break;
+            }
+            if error > 0. {
+                high = t;
+            } else {
+                low = t;
+            }
+            let speed = self.derivative(t).distance(Vec2::ZERO);
+            t -= error / speed;
+            if speed == 0. || t <= low || t >= high {
+                t = (low + high) / 2.;
+            }
+        }
+        t
+    }
+
+    /// Return the top-left and bottom-right corners of the smallest
+    /// axis-aligned rectangle containing the curve.
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        let (p0, p1) = (self.start(), self.end());
+        let mut min = Vec2::new(p0.x.min(p1.x), p0.y.min(p1.y));
+        let mut max = Vec2::new(p0.x.max(p1.x), p0.y.max(p1.y));
+        for t in self.extrema() {
+            let p = self.eval(t);
+            min = Vec2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vec2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        (min, max)
+    }
+
+    /// Return the values of `t` inside `(0, 1)` where the curve is horizontal or vertical.
+    fn extrema(&self) -> Vec<f64> {
+        let mut roots = vec![];
+        let axes = |p: Vec2| [p.x, p.y];
+        match *self {
+            Curve::Quadratic(p0, c, p1) => {
+                for ((p0, c), p1) in axes(p0).into_iter().zip(axes(c)).zip(axes(p1)) {
+                    let denominator = p0 - 2. * c + p1;
+                    if denominator != 0. {
+                        roots.push((p0 - c) / denominator);
+                    }
+                }
+            }
+            Curve::Cubic(p0, c1, c2, p1) => {
+                let points = axes(p0)
+                    .into_iter()
+                    .zip(axes(c1))
+                    .zip(axes(c2))
+                    .zip(axes(p1));
+                for (((p0, c1), c2), p1) in points {
+                    // The derivative is a * t^2 + b * t + c
+                    let a = 3. * (-p0 + 3. * c1 - 3. * c2 + p1);
+                    let b = 6. * (p0 - 2. * c1 + c2);
+                    let c = 3. * (c1 - p0);
+                    if a.abs() < 1e-12 {
+                        if b != 0. {
+                            roots.push(-c / b);
+                        }
+                        continue;
+                    }
+                    let discriminant = b * b - 4. * a * c;
+                    if discriminant >= 0. {
+                        let sqrt = discriminant.sqrt();
+                        roots.push((-b + sqrt) / (2. * a));
+                        roots.push((-b - sqrt) / (2. * a));
+                    }
+                }
+            }
+        }
+        roots.retain(|t| *t > 0. && *t < 1.);
+        roots
+    }
+
+    /// Append to `points` a polyline approximating the curve, excluding its
+    /// start, never deviating from the curve by more than `tolerance`.
+    pub fn flatten_into(&self, tolerance: f64, points: &mut Vec<Vec2>) {
+        self.subdivide(tolerance, 0, points);
+    }
+
+    fn subdivide(&self, tolerance: f64, depth: usize, points: &mut Vec<Vec2>) {
+        // Upper bound of the distance between the curve and its chord, the
+        // curve being a blend of the points with the controls weighing at
+        // most 1/2 or 3/4
+        let deviation = match *self {
+            Curve::Quadratic(p0, c, p1) => c.distance_to_segment(p0, p1) / 2.,
+            Curve::Cubic(p0, c1, c2, p1) => {
+                c1.distance_to_segment(p0, p1)
+                    .max(c2.distance_to_segment(p0, p1))
+                    * 0.75
+            }
+        };
+        if deviation <= tolerance || depth == MAX_DEPTH {
+            points.push(self.end());
+            return;
+        }
+        let (first, second) = self.split(0.5);
+        first.subdivide(tolerance, depth + 1, points);
+        second.subdivide(tolerance, depth + 1, points);
+    }
+}
+
+/// A path made of one or more contiguous Bézier curves.
+///
+/// The path is parametrised by `t` in the `[0, 1]` range, every curve
+/// taking an equal share of it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bezier {
+    curves: Vec<Curve>,
+}
+
+impl Bezier {
+    /// Return the path made of `curves`, or `None` when there are none.
+    pub fn new(curves: Vec<Curve>) -> Option<Self> {
+        (!curves.is_empty()).then_some(Self { curves })
+    }
+
+    pub fn quadratic(start: Vec2, control: Vec2, end: Vec2) -> Self {
+        Self {
+            curves: vec![Curve::Quadratic(start, control, end)],
+        }
+    }
+
+    pub fn cubic(start: Vec2, control1: Vec2, control2: Vec2, end: Vec2) -> Self {
+        Self {
+            curves: vec![Curve::Cubic(start, control1, control2, end)],
+        }
+    }
+
+    /// Return the curves of the path, at least one.
+    pub fn curves(&self) -> &[Curve] {
+        &self.curves
+    }
+
+    /// Continue the path with a quadratic curve.
+    pub fn quad_to(mut self, control: Vec2, end: Vec2) -> Self {
+        self.curves.push(Curve::Quadratic(self.end(), control, end));
+        self
+    }
+
+    /// Continue the path with a cubic curve.
+    pub fn cubic_to(mut self, control1: Vec2, control2: Vec2, end: Vec2) -> Self {
+        self.curves
+            .push(Curve::Cubic(self.end(), control1, control2, end));
+        self
+    }
+
+    pub fn start(&self) -> Vec2 {
+        self.curves[0].start()
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.curves[self.curves.len() - 1].end()
+    }
+
+    /// Return the index of the curve at `t` and the parameter along that curve.
+    fn locate(&self, t: f64) -> (usize, f64) {
+        let n = self.curves.len();
+        let scaled = t.clamp(0., 1.) * n as f64;
+        let index = usize::min(scaled.floor() as usize, n - 1);
+        (index, scaled - index as f64)
+    }
+
+    /// Return the point of the path at `t`.
+    pub fn eval(&self, t: f64) -> Vec2 {
+        let (i, t) = self.locate(t);
+        self.curves[i].eval(t)
+    }
+
+    /// Return the first derivative of the path at `t`.
+    pub fn derivative(&self, t: f64) -> Vec2 {
+        let (i, t) = self.locate(t);
+        self.curves[i].derivative(t) * self.curves.len() as f64
+    }
+
+    pub fn length(&self) -> f64 {
+        self.curves.iter().map(|c| c.length()).sum()
+    }
+
+    /// Return the `t` at which the length of the path from its start is `distance`.
+    ///
+    /// Stepping `distance` by a constant amount yields points evenly spaced
+    /// along the path, whatever the spacing of the control points.
+    pub fn t_at_length(&self, distance: f64) -> f64 {
+        let n = self.curves.len() as f64;
+        let mut travelled = 0.;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let length = curve.length();
+            if travelled + length >= distance {
+                return (i as f64 + curve.t_at_length(distance - travelled)) / n;
+            }
+            travelled += length;
+        }
+        1.
+    }
+
+    /// Return the point at `distance` from the start, measured along the path.
+    pub fn point_at_length(&self, distance: f64) -> Vec2 {
+        self.eval(self.t_at_length(distance))
+    }
+
+    /// Split the path at `t` into two paths.
+    pub fn split(&self, t: f64) -> (Bezier, Bezier) {
+        let (i, local) = self.locate(t);
+        let (first, second) = self.curves[i].split(local);
+        let mut head = self.curves[..i].to_vec();
+        head.push(first);
+        let mut tail = vec![second];
+        tail.extend_from_slice(&self.curves[i + 1..]);
+        (Bezier { curves: head }, Bezier { curves: tail })
+    }
+
+    /// Return the same path, traced from the end to the start.
+    pub fn reverse(&self) -> Bezier {
+        Bezier {
+            curves: self.curves.iter().rev().map(|c| c.reverse()).collect(),
+        }
+    }
+
+    /// Approximate the path with a polyline never deviating from it by more than `tolerance`.
+    pub fn flatten(&self, tolerance: f64) -> LineString {
+        let mut points = vec![self.start()];
+        for curve in &self.curves {
+            if points[points.len() - 1] != curve.start() {
+                points.push(curve.start());
+            }
+            curve.flatten_into(tolerance, &mut points);
+        }
+        LineString::new(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shapes::bezier::{Bezier, Curve};
+    use crate::traits::{BoundingBox, Lerp};
+    use crate::vec2::Vec2;
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    fn arch() -> Bezier {
+        Bezier::cubic(
+            Vec2::new(0., 0.),
+            Vec2::new(0., 40.),
+            Vec2::new(100., 40.),
+            Vec2::new(100., 0.),
+        )
+    }
+
+    #[rstest]
+    #[case(0., Vec2::new(0., 0.))]
+    #[case(0.5, Vec2::new(50., 30.))]
+    #[case(1., Vec2::new(100., 0.))]
+    fn eval(#[case] t: f64, #[case] expected: Vec2) {
+        assert_eq!(arch().eval(t), expected);
+    }
+
+    #[test]
+    fn derivative_matches_finite_differences() {
+        let bezier = arch().quad_to(Vec2::new(150., -50.), Vec2::new(200., 0.));
+        for t in [0.1, 0.4, 0.75] {
+            let h = 1e-6;
+            let expected = (bezier.eval(t + h) - bezier.eval(t - h)) / (2. * h);
+            let derivative = bezier.derivative(t);
+            assert_relative_eq!(derivative.x, expected.x, epsilon = 1e-3);
+            assert_relative_eq!(derivative.y, expected.y, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn length_of_straight_curves() {
+        let line = Bezier::quadratic(Vec2::new(0., 0.), Vec2::new(1., 1.), Vec2::new(3., 3.));
+        assert_relative_eq!(line.length(), 18_f64.sqrt(), epsilon = 1e-9);
+        // The control point is not in the middle: t and length are not proportional
+        let t = line.t_at_length(line.length() / 2.);
+        assert_relative_eq!(line.eval(t).x, 1.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn points_at_length_are_evenly_spaced() {
+        let bezier = arch().cubic_to(
+            Vec2::new(100., -80.),
+            Vec2::new(300., 0.),
+            Vec2::new(200., 50.),
+        );
+        let step = bezier.length() / 20.;
+        let flattened = bezier.flatten(0.0001);
+        let mut travelled = 0.;
+        let mut checkpoint = step;
+        for pair in flattened.points.windows(2) {
+            let length = pair[0].distance(pair[1]);
+            while travelled + length >= checkpoint && checkpoint < bezier.length() {
+                let expected = pair[0].lerp(pair[1], (checkpoint - travelled) / length);
+                assert!(bezier.point_at_length(checkpoint).distance(expected) < 0.01);
+                checkpoint += step;
+            }
+            travelled += length;
+        }
+        assert!(checkpoint > bezier.length() - step);
+    }
+
+    #[test]
+    fn empty_paths_are_rejected() {
+        assert!(Bezier::new(vec![]).is_none());
+        let path = Bezier::new(arch().curves().to_vec()).unwrap();
+        assert_eq!(path.start(), arch().start());
+    }
+
+    #[test]
+    fn split() {
+        let bezier = arch().quad_to(Vec2::new(150., -50.), Vec2::new(200., 0.));
+        let (head, tail) = bezier.split(0.25);
+        assert_eq!(head.curves.len(), 1);
+        assert_eq!(tail.curves.len(), 2);
+        assert_eq!(head.end(), bezier.eval(0.25));
+        assert_eq!(tail.start(), bezier.eval(0.25));
+        let p = head.eval(0.5);
+        let expected = bezier.eval(0.125);
+        assert_relative_eq!(p.x, expected.x, epsilon = 1e-9);
+        assert_relative_eq!(p.y, expected.y, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn bbox_includes_extrema() {
+        let bbox = arch()
+            .quad_to(Vec2::new(150., -40.), Vec2::new(200., 0.))
+            .bbox();
+        assert_relative_eq!(bbox.xy.x, 0.);
+        assert_relative_eq!(bbox.xy.y, -20.);
+        assert_relative_eq!(bbox.width, 200.);
+        assert_relative_eq!(bbox.height, 50.);
+    }
+
+    #[test]
+    fn flatten_within_tolerance() {
+        let curves = [
+            (
+                Curve::Cubic(
+                    Vec2::new(0., 0.),
+                    Vec2::new(0., 40.),
+                    Vec2::new(100., 40.),
+                    Vec2::new(100., 0.),
+                ),
+                10,
+            ),
+            // Overshooting the chord along its direction
+            (
+                Curve::Cubic(
+                    Vec2::new(0., 0.),
+                    Vec2::new(-10., 0.),
+                    Vec2::new(20., 0.),
+                    Vec2::new(10., 0.),
+                ),
+                2,
+            ),
+            (
+                Curve::Quadratic(Vec2::new(0., 0.), Vec2::new(20., 0.), Vec2::new(10., 0.)),
+                2,
+            ),
+        ];
+        for (curve, min_points) in curves {
+            let points = Bezier::new(vec![curve]).unwrap().flatten(0.01).points;
+            assert!(points.len() > min_points);
+            for i in 0..=1000 {
+                // Every point of the curve lies close to the polyline
+                let p = curve.eval(i as f64 / 1000.);
+                let distance = points
+                    .windows(2)
+                    .map(|pair| {
+                        let (a, b) = (pair[0], pair[1]);
+                        let ab = b - a;
+                        let t = (((p - a) * ab).sum() / (ab * ab).sum()).clamp(0., 1.);
+                        p.distance(a + ab * t)
+                    })
+                    .fold(f64::MAX, f64::min);
+                assert!(distance <= 0.01);
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::f64::consts::TAU;
+use std::f64::consts::{PI, TAU};
 
 use crate::shapes::polygon::Polygon;
 use crate::vec2::Vec2;
@@ -26,6 +26,16 @@ impl Circle {
         )
     }
 
+    /// Return the number of points needed by `to_polygon` so that no chord
+    /// deviates from the circumference by more than `tolerance`.
+    pub fn points_for_tolerance(&self, tolerance: f64) -> usize {
+        if tolerance >= self.radius {
+            return 8;
+        }
+        let n = (PI / (1. - tolerance / self.radius).acos()).ceil() as usize;
+        usize::max(8, n)
+    }
+
     pub fn to_polygon(&self, points: usize) -> Polygon {
         let mut pvec = vec![];
         for i in 0..points {
@@ -34,6 +44,6 @@ impl Circle {
             let y = angle.sin() * self.radius + self.center.y;
             pvec.push(Vec2 { x, y });
         }
-        Polygon { points: pvec }
+        Polygon::new(pvec)
     }
 }
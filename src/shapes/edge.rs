@@ -15,6 +15,7 @@ impl PartialEq for Edge {
 impl Eq for Edge {}
 
 #[cfg(test)]
+#[allow(clippy::too_many_arguments)]
 mod tests {
     use crate::prelude::Vec2;
     use crate::shapes::edge::Edge;
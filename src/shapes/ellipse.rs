@@ -0,0 +1,78 @@
+use std::f64::consts::TAU;
+
+use crate::angle::Angle;
+use crate::shapes::arc::segments_for_tolerance;
+use crate::shapes::linestring::LineString;
+use crate::vec2::Vec2;
+
+/// An ellipse represented by a center, two radii and a rotation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ellipse {
+    /// The center point
+    pub center: Vec2,
+    /// The radius along the x axis, before rotation
+    pub rx: f64,
+    /// The radius along the y axis, before rotation
+    pub ry: f64,
+    /// The rotation angle
+    pub rotation: Angle,
+}
+
+impl Ellipse {
+    pub fn new(center: Vec2, rx: f64, ry: f64, rotation: Angle) -> Self {
+        Self {
+            center,
+            rx,
+            ry,
+            rotation,
+        }
+    }
+
+    /// Return the point of the ellipse at the parametric `angle`.
+    pub fn point_at(&self, angle: Angle) -> Vec2 {
+        let p = Vec2::new(self.rx * angle.cos(), self.ry * angle.sin());
+        self.center + p.rotate(self.rotation)
+    }
+
+    /// Approximate the ellipse with a closed polyline whose chords deviate
+    /// from it by at most `tolerance`.
+    pub fn to_linestring(&self, tolerance: f64) -> LineString {
+        // The ellipse is a circle of the larger radius squashed along one
+        // axis: the squashing never increases the deviation of its chords.
+        let n = segments_for_tolerance(self.rx.max(self.ry), TAU, tolerance).max(8);
+        let mut points = (0..n)
+            .map(|i| self.point_at(Angle::from_radians(TAU * i as f64 / n as f64)))
+            .collect::<Vec<Vec2>>();
+        points.push(points[0]);
+        LineString::new(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::traits::BoundingBox;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn bbox_of_rotated_ellipse() {
+        let ellipse = Ellipse::new(Vec2::new(10., 20.), 4., 2., Angle::from_degrees(90.));
+        let bbox = ellipse.bbox();
+        assert_relative_eq!(bbox.xy.x, 8., epsilon = 1e-9);
+        assert_relative_eq!(bbox.xy.y, 16., epsilon = 1e-9);
+        assert_relative_eq!(bbox.width, 4., epsilon = 1e-9);
+        assert_relative_eq!(bbox.height, 8., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn to_linestring_is_closed_and_on_the_ellipse() {
+        let ellipse = Ellipse::new(Vec2::new(5., 5.), 30., 10., Angle::from_degrees(30.));
+        let line = ellipse.to_linestring(0.05);
+        assert_eq!(line.points[0], line.points[line.points.len() - 1]);
+        for p in line.points {
+            let q = (p - ellipse.center).rotate(Angle::from_degrees(-30.));
+            let r = (q.x / 30.).powi(2) + (q.y / 10.).powi(2);
+            assert_relative_eq!(r, 1., epsilon = 1e-9);
+        }
+    }
+}
@@ -1,8 +1,7 @@
-use crate::traits::ToGeoLineString;
+use crate::traits::ToGeoPolygon;
 use crate::vec2::Vec2;
-use geo::algorithm::bool_ops::BooleanOps;
-use geo::Coord;
-use geo::MultiPolygon as GeoMultiPolygon;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
 
 /// A series of contiguous line segments represented by two or more points
 #[derive(Clone, PartialEq)]
@@ -29,27 +28,89 @@ impl LineString {
         self.clone()
     }
 
-    pub fn clip<T: ToGeoLineString>(&self, other: &T, invert: bool) -> Vec<LineString> {
-        let poly = geo::Polygon::new(other.to_geo_linestring(), vec![]);
-        let mpoly = GeoMultiPolygon::new(vec![poly]);
-        let clipped = mpoly.clip(&self.to_geo_multilinestring(), invert);
-        let mut res = vec![];
-        clipped.0.iter().for_each(|l| {
-            let mut points: Vec<Coord> = vec![];
-            l.clone().into_points().iter().for_each(|p| {
-                points.push(p.0);
-            });
-            res.push(LineString::new(
-                points
-                    .iter()
-                    .map(|c| Vec2 { x: c.x, y: c.y })
-                    .collect::<Vec<Vec2>>(),
-            ));
-        });
-        res
+    /// Return the parts of the line inside `other`, or outside of it when
+    /// `invert` is true, in the order and direction they are traversed.
+    /// Holes are outside, and parts running along the boundary are inside.
+    pub fn clip<T: ToGeoPolygon>(&self, other: &T, invert: bool) -> Vec<LineString> {
+        let poly = other.to_geo_polygon();
+        let edges = std::iter::once(poly.exterior())
+            .chain(poly.interiors())
+            .flat_map(|ring| {
+                ring.lines()
+                    .map(|l| (Vec2::new(l.start.x, l.start.y), Vec2::new(l.end.x, l.end.y)))
+            })
+            .collect::<Vec<(Vec2, Vec2)>>();
+        let tree = RTree::bulk_load(
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (a, b))| Edge::new(Rectangle::from_corners([a.x, a.y], [b.x, b.y]), i))
+                .collect(),
+        );
+        if edges.is_empty() {
+            return if invert { vec![self.clone()] } else { vec![] };
+        }
+        let bounds = tree.root().envelope();
+        let (lower, upper) = (bounds.lower(), bounds.upper());
+        // How close to an edge a point is on it, relative to the size of the shape
+        let epsilon = 1e-9 * (upper[0] - lower[0]).max(upper[1] - lower[1]).max(1.);
+        let inside = |p: Vec2| {
+            let near = AABB::from_corners(
+                [p.x - epsilon, p.y - epsilon],
+                [p.x + epsilon, p.y + epsilon],
+            );
+            if tree
+                .locate_in_envelope_intersecting(&near)
+                .any(|e| p.distance_to_segment(edges[e.data].0, edges[e.data].1) <= epsilon)
+            {
+                return true;
+            }
+            // Even-odd rule, so that holes are outside
+            let ray = AABB::from_corners([p.x, p.y], [upper[0] + 1., p.y]);
+            tree.locate_in_envelope_intersecting(&ray)
+                .filter(|e| p.ray_crosses(edges[e.data].0, edges[e.data].1))
+                .count()
+                % 2
+                == 1
+        };
+
+        let mut parts = vec![];
+        let mut current: Vec<Vec2> = vec![];
+        for pair in self.points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let envelope =
+                AABB::from_corners([a.x.min(b.x), a.y.min(b.y)], [a.x.max(b.x), a.y.max(b.y)]);
+            let mut cuts = vec![0., 1.];
+            cuts.extend(
+                tree.locate_in_envelope_intersecting(&envelope)
+                    .flat_map(|e| crossings(a, b, edges[e.data].0, edges[e.data].1)),
+            );
+            cuts.sort_by(f64::total_cmp);
+            cuts.dedup_by(|t, u| *t - *u < 1e-12);
+            let at = |t: f64| match t {
+                0. => a,
+                1. => b,
+                _ => a + (b - a) * t,
+            };
+            for piece in cuts.windows(2) {
+                let (p, q) = (at(piece[0]), at(piece[1]));
+                if inside((p + q) / 2.) != invert {
+                    if current.is_empty() {
+                        current.push(p);
+                    }
+                    current.push(q);
+                } else if !current.is_empty() {
+                    parts.push(LineString::new(std::mem::take(&mut current)));
+                }
+            }
+        }
+        if !current.is_empty() {
+            parts.push(LineString::new(current));
+        }
+        parts
     }
 
-    pub fn clip_many<T: ToGeoLineString>(&self, others: &[T], invert: bool) -> Vec<LineString> {
+    pub fn clip_many<T: ToGeoPolygon>(&self, others: &[T], invert: bool) -> Vec<LineString> {
         let mut retval = vec![self.clone()];
         others.iter().for_each(|other| {
             retval = retval
@@ -60,3 +121,126 @@ impl LineString {
         retval
     }
 }
+
+// An edge of the clipping polygon in the spatial index
+type Edge = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// Return where the segment from `a` to `b` meets the one from `c` to `d`,
+/// as fractions of the way from `a` to `b`: where they cross, or where the
+/// overlap starts and ends when they lie along each other.
+fn crossings(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Vec<f64> {
+    let (r, s, ca) = (b - a, d - c, c - a);
+    let denom = r.x * s.y - r.y * s.x;
+    if denom == 0. {
+        let length = r.x * r.x + r.y * r.y;
+        if length == 0. || ca.x * r.y - ca.y * r.x != 0. {
+            return vec![];
+        }
+        let da = d - a;
+        return [ca, da]
+            .iter()
+            .map(|v| (v.x * r.x + v.y * r.y) / length)
+            .filter(|t| (0. ..=1.).contains(t))
+            .collect();
+    }
+    let t = (ca.x * s.y - ca.y * s.x) / denom;
+    let u = (ca.x * r.y - ca.y * r.x) / denom;
+    if (0. ..=1.).contains(&t) && (0. ..=1.).contains(&u) {
+        vec![t]
+    } else {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use approx::assert_relative_eq;
+
+    fn square() -> Rect {
+        Rect::new(Vec2::new(0., 0.), 10., 10.)
+    }
+
+    fn length(lines: &[LineString]) -> f64 {
+        lines
+            .iter()
+            .flat_map(|l| l.points.windows(2))
+            .map(|w| w[0].distance(w[1]))
+            .sum()
+    }
+
+    #[test]
+    fn clip_keeps_the_inside_in_order() {
+        let line = LineString::new(vec![
+            Vec2::new(-5., 2.),
+            Vec2::new(15., 2.),
+            Vec2::new(15., 8.),
+            Vec2::new(-5., 8.),
+        ]);
+        let parts = line.clip(&square(), false);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].points, vec![Vec2::new(0., 2.), Vec2::new(10., 2.)]);
+        assert_eq!(parts[1].points, vec![Vec2::new(10., 8.), Vec2::new(0., 8.)]);
+        // The outside is what is left
+        let outside = line.clip(&square(), true);
+        assert_eq!(outside.len(), 3);
+        assert_relative_eq!(length(&outside), length(&[line]) - 20.);
+    }
+
+    #[test]
+    fn holes_are_outside() {
+        let frame = Polygon::with_interiors(
+            square().to_polygon().points,
+            vec![Rect::new(Vec2::new(3., 3.), 4., 4.).to_polygon().points],
+        );
+        let line = LineString::line(Vec2::new(-5., 5.), Vec2::new(15., 5.));
+        let parts = line.clip(&frame, false);
+        assert_eq!(parts.len(), 2);
+        assert_relative_eq!(length(&parts), 6.);
+        let outside = line.clip(&frame, true);
+        assert_eq!(outside.len(), 3);
+        assert_relative_eq!(length(&outside), 14.);
+    }
+
+    #[test]
+    fn lines_along_edges_are_inside() {
+        for y in [0., 10.] {
+            let line = LineString::line(Vec2::new(-5., y), Vec2::new(15., y));
+            let parts = line.clip(&square(), false);
+            assert_eq!(parts.len(), 1);
+            assert_eq!(parts[0].points, vec![Vec2::new(0., y), Vec2::new(10., y)]);
+            assert_relative_eq!(length(&line.clip(&square(), true)), 10.);
+        }
+    }
+
+    #[test]
+    fn lines_through_vertices() {
+        let diagonal = LineString::line(Vec2::new(-5., -5.), Vec2::new(15., 15.));
+        let parts = diagonal.clip(&square(), false);
+        assert_eq!(parts.len(), 1);
+        assert_eq!(
+            parts[0].points,
+            vec![Vec2::new(0., 0.), Vec2::new(10., 10.)]
+        );
+        // Grazing a corner from the outside
+        let grazing = LineString::line(Vec2::new(5., -5.), Vec2::new(15., 5.));
+        assert!(grazing.clip(&square(), false).is_empty());
+        let outside = grazing.clip(&square(), true);
+        assert_eq!(outside.len(), 1);
+        assert_relative_eq!(length(&outside), length(&[grazing]));
+    }
+
+    #[test]
+    fn self_overlapping_lines_are_clipped_each_time() {
+        let line = LineString::new(vec![
+            Vec2::new(-5., 5.),
+            Vec2::new(15., 5.),
+            Vec2::new(-5., 5.),
+            Vec2::new(15., 5.),
+        ]);
+        let parts = line.clip(&square(), false);
+        assert_eq!(parts.len(), 3);
+        assert_relative_eq!(length(&parts), 30.);
+        assert_relative_eq!(length(&line.clip(&square(), true)), 30.);
+    }
+}
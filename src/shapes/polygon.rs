@@ -1,47 +1,63 @@
 use crate::shapes::edge::Edge;
 use crate::shapes::LineString;
-use crate::traits::ToGeoLineString;
+use crate::traits::boolean::Boolean;
+use crate::traits::{ToGeoLineString, ToGeoPolygon};
 use crate::vec2::Vec2;
 use geo::algorithm::bool_ops::BooleanOps;
 use geo::Coord;
 use geo::MultiPolygon as GeoMultiPolygon;
 
-/// A bounded area represented by a LineString exterior ring
+/// A bounded area represented by an exterior ring and zero or more interior
+/// rings, the holes. Overlapping holes are combined with the even-odd rule.
 #[derive(Clone, PartialEq, Debug)]
 pub struct Polygon {
+    /// The exterior ring
     pub points: Vec<Vec2>,
+    /// The interior rings
+    pub interiors: Vec<Vec<Vec2>>,
 }
 
 impl Polygon {
     pub fn new(points: Vec<Vec2>) -> Self {
-        Self { points }
+        Self {
+            points,
+            interiors: vec![],
+        }
+    }
+
+    pub fn with_interiors(points: Vec<Vec2>, interiors: Vec<Vec<Vec2>>) -> Self {
+        Self { points, interiors }
     }
 
     pub fn triangle(p1: Vec2, p2: Vec2, p3: Vec2) -> Self {
-        Self {
-            points: vec![p1, p2, p3],
-        }
+        Self::new(vec![p1, p2, p3])
     }
 
     pub fn add_vec(&mut self, vec: Vec2) -> Self {
-        self.points.iter_mut().for_each(|p| {
-            p.x += vec.x;
-            p.y += vec.y;
-        });
+        self.points
+            .iter_mut()
+            .chain(self.interiors.iter_mut().flatten())
+            .for_each(|p| {
+                p.x += vec.x;
+                p.y += vec.y;
+            });
         self.clone()
     }
 
+    /// Return the exterior ring as a closed LineString.
     pub fn to_linestring(&self) -> LineString {
-        let mut points = self.points.clone();
-        if points[0] != points[points.len() - 1] {
-            points.push(points[0]);
-        }
-        LineString::new(points)
+        close_ring(&self.points)
+    }
+
+    /// Return the exterior ring, followed by the interior rings, as closed LineStrings.
+    pub fn rings(&self) -> Vec<LineString> {
+        let mut rings = vec![self.to_linestring()];
+        rings.extend(self.interiors.iter().map(|ring| close_ring(ring)));
+        rings
     }
 
-    pub fn clip<T: ToGeoLineString>(&self, other: &T, invert: bool) -> Vec<LineString> {
-        let poly = geo::Polygon::new(other.to_geo_linestring(), vec![]);
-        let mpoly = GeoMultiPolygon::new(vec![poly]);
+    pub fn clip<T: ToGeoPolygon>(&self, other: &T, invert: bool) -> Vec<LineString> {
+        let mpoly = GeoMultiPolygon::new(vec![other.to_geo_polygon()]);
         let clipped = mpoly.clip(&self.to_geo_multilinestring(), invert);
         let mut res = vec![];
         clipped.0.iter().for_each(|l| {
@@ -59,8 +75,8 @@ impl Polygon {
         res
     }
 
-    pub fn clip_many<T: ToGeoLineString>(&self, others: &[T], invert: bool) -> Vec<LineString> {
-        let mut retval = vec![self.to_linestring()];
+    pub fn clip_many<T: ToGeoPolygon>(&self, others: &[T], invert: bool) -> Vec<LineString> {
+        let mut retval = self.rings();
         others.iter().for_each(|other| {
             retval = retval
                 .iter()
@@ -70,6 +86,7 @@ impl Polygon {
         retval
     }
 
+    /// Return the edges of the exterior ring.
     pub fn edges(&self) -> Vec<Edge> {
         let mut edges = vec![];
         for i in 0..self.points.len() {
@@ -97,30 +114,127 @@ impl Polygon {
         common
     }
 
-    // Merge two adjacent (they shares at least one edge),
-    // non-overlapping polygons.
-    // The method doesn't check the preconditions: is up
-    // to the caller to ensure preconditions are met.
+    /// Merge two adjacent (they share at least one edge),
+    /// non-overlapping polygons.
+    #[deprecated(note = "use `Boolean::union`, which handles any pair of polygons")]
     pub fn merge_adjacent(&self, adj: &Self) -> Self {
-        let mut union_points = vec![];
-        union_points.append(&mut self.points.clone());
-        union_points.append(&mut adj.points.clone());
+        self.union(adj)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| self.clone())
+    }
+}
 
-        let psum: Vec2 = union_points.iter().copied().sum();
-        let center = psum / union_points.len() as f64;
+impl From<&geo::Polygon> for Polygon {
+    fn from(poly: &geo::Polygon) -> Self {
+        Polygon::with_interiors(
+            open_ring(poly.exterior()),
+            poly.interiors().iter().map(open_ring).collect(),
+        )
+    }
+}
 
-        union_points.sort_by_key(|p| (*p - center).to_polar().0);
-        union_points.dedup_by_key(|p| (*p - center).to_polar().0);
+// Geo rings repeat the first point at the end, ours don't.
+fn open_ring(ring: &geo::LineString) -> Vec<Vec2> {
+    let mut points = ring
+        .coords()
+        .map(|c| Vec2 { x: c.x, y: c.y })
+        .collect::<Vec<Vec2>>();
+    if points.len() > 1 && points[0] == points[points.len() - 1] {
+        points.pop();
+    }
+    points
+}
 
-        Polygon::new(union_points)
+fn close_ring(points: &[Vec2]) -> LineString {
+    let mut points = points.to_vec();
+    if points[0] != points[points.len() - 1] {
+        points.push(points[0]);
     }
+    LineString::new(points)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::shapes::edge::Edge;
+    use crate::shapes::linestring::LineString;
     use crate::shapes::polygon::Polygon;
+    use crate::traits::{Centroid, Contains, Sample, ScaleDist};
     use crate::vec2::Vec2;
+    use approx::assert_relative_eq;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn square(xy: f64, side: f64) -> Vec<Vec2> {
+        vec![
+            Vec2::new(xy, xy),
+            Vec2::new(xy + side, xy),
+            Vec2::new(xy + side, xy + side),
+            Vec2::new(xy, xy + side),
+        ]
+    }
+
+    fn frame() -> Polygon {
+        Polygon::with_interiors(square(0., 30.), vec![square(10., 10.)])
+    }
+
+    #[test]
+    fn holes_are_not_contained() {
+        assert!(frame().contains(&Vec2::new(5., 5.)));
+        assert!(!frame().contains(&Vec2::new(15., 15.)));
+        let samples = frame().sample_uniform(&mut StdRng::seed_from_u64(42), 200);
+        assert!(samples
+            .iter()
+            .all(|p| !(p.x > 10. && p.x < 20. && p.y > 10. && p.y < 20.)));
+    }
+
+    #[test]
+    fn clip_to_a_frame() {
+        let line = LineString::line(Vec2::new(-5., 15.), Vec2::new(35., 15.));
+        let clipped = line.clip(&frame(), false);
+        assert_eq!(clipped.len(), 2);
+        let length: f64 = clipped
+            .iter()
+            .map(|l| l.points[0].distance(l.points[l.points.len() - 1]))
+            .sum();
+        assert_relative_eq!(length, 20.);
+    }
+
+    #[test]
+    fn scale_dist_moves_holes() {
+        let shrunk = frame().scale_dist(1.).unwrap();
+        let hole = &shrunk.interiors[0];
+        let xs = hole.iter().map(|p| p.x);
+        assert_relative_eq!(xs.clone().fold(f64::MAX, f64::min), 9.);
+        assert_relative_eq!(xs.fold(f64::MIN, f64::max), 21.);
+        assert_relative_eq!(shrunk.points[0].x, 1.);
+    }
+
+    #[test]
+    fn scale_dist_vanishes() {
+        assert!(frame().scale_dist(8.).is_none());
+        assert!(frame().scale_dist(-8.).is_some());
+    }
+
+    #[test]
+    fn centroid_respects_holes() {
+        assert_eq!(
+            Polygon::new(square(0., 30.)).centroid(),
+            Vec2::new(15., 15.)
+        );
+        // A 30x30 square less a 5x5 one in its corner
+        let off_center = Polygon::with_interiors(square(0., 30.), vec![square(2., 5.)]);
+        let expected = (15. * 900. - 4.5 * 25.) / 875.;
+        assert_relative_eq!(off_center.centroid().x, expected);
+        assert_relative_eq!(off_center.centroid().y, expected);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn merging_degenerate_polygons_keeps_the_first() {
+        let line = Polygon::new(vec![Vec2::new(0., 0.), Vec2::new(10., 0.)]);
+        assert_eq!(line.merge_adjacent(&line), line);
+    }
 
     #[test]
     fn edges_in_common() {
@@ -88,7 +88,7 @@ impl Rect {
                 y: self.xy.y + self.height,
             },
         ];
-        Polygon { points }
+        Polygon::new(points)
     }
 
     pub fn into_square_grid(&self, square_side: f64) -> SquareGrid {
@@ -121,6 +121,8 @@ impl ScalePerc for Rect {
 }
 
 impl ScaleDist for Rect {
+    type Output = Self;
+
     fn scale_dist(&self, distance: f64) -> Self {
         Rect::new(
             self.xy + distance,
@@ -1,8 +1,9 @@
-use std::time::Instant;
-
 use crate::group::Group;
-use crate::render::render_svg;
+use crate::optimize::PathOrderReport;
+use crate::raster::render_raster;
+use crate::render::{render_gcode, render_hpgl, render_svg, GcodeConfig};
 use crate::shapes::rectangle::Rect;
+use crate::stats::{GroupStats, PlotterSpeed, SketchStats};
 use crate::traits::Centroid;
 use crate::uom::Uom;
 use crate::vec2::Vec2;
@@ -12,12 +13,19 @@ use anyhow::Result;
 use svg::Document;
 
 use crate::layout::PageLayout;
+use crate::optimize::dedup::remove_duplicate_segments;
 
 pub enum Debug {
     Off,
     On,
 }
 
+/// Whether `Sketch::render` reorders paths to reduce pen-up travel
+pub enum Optimization {
+    Off,
+    On,
+}
+
 /// A high-level representation of a plotter drawing
 pub struct Sketch {
     pub layout: PageLayout,
@@ -25,7 +33,8 @@ pub struct Sketch {
     pub uom: Uom,
     doc: Document,
     debug: Debug,
-    created: Instant,
+    optimization: Optimization,
+    path_order: Vec<(usize, PathOrderReport)>,
 }
 
 impl Sketch {
@@ -48,10 +57,17 @@ impl Sketch {
             doc: Document::new(),
             uom,
             debug,
-            created: Instant::now(),
+            optimization: Optimization::On,
+            path_order: vec![],
         }
     }
 
+    /// Enable or disable path optimization when rendering
+    pub fn set_optimization(&mut self, optimization: Optimization) -> &mut Self {
+        self.optimization = optimization;
+        self
+    }
+
     pub fn group(&mut self, index: usize) -> &mut Group {
         &mut self.groups[index]
     }
@@ -119,18 +135,65 @@ impl Sketch {
         f64::min(self.width(), self.height())
     }
 
+    /// Remove the segments drawn more than once, within `tolerance`, by groups
+    /// sharing the same pen. A segment shared by several groups is kept in the first one.
+    pub fn remove_duplicate_segments(&mut self, tolerance: f64) {
+        let mut done = vec![false; self.groups.len()];
+        for i in 0..self.groups.len() {
+            if done[i] {
+                continue;
+            }
+            let same_pen = (i..self.groups.len())
+                .filter(|&j| self.groups[j].style == self.groups[i].style)
+                .collect::<Vec<usize>>();
+            let layers = same_pen
+                .iter()
+                .map(|&j| std::mem::take(&mut self.groups[j].elements))
+                .collect();
+            let layers = remove_duplicate_segments(layers, tolerance);
+            for (&j, elements) in same_pen.iter().zip(layers) {
+                self.groups[j].elements = elements;
+                done[j] = true;
+            }
+        }
+    }
+
+    /// Compute distances, pen lifts and an estimate of the plotting time for every non-empty group
+    pub fn stats(&self, speed: &PlotterSpeed) -> SketchStats {
+        SketchStats {
+            groups: self
+                .groups
+                .iter()
+                .enumerate()
+                .filter(|(_, g)| !g.elements.is_empty())
+                .map(|(id, g)| (id, GroupStats::new(g, self.uom, speed)))
+                .collect(),
+        }
+    }
+
+    /// Return the outcome of the path ordering of each non-empty group, by
+    /// group index, done by the last `render` when optimization is on
+    pub fn path_order_reports(&self) -> &[(usize, PathOrderReport)] {
+        &self.path_order
+    }
+
     pub fn render(&mut self) -> &Self {
-        let new_to_render = self.created.elapsed();
-        println!(
-            "Time elapsed from new(): {} milliseconds",
-            new_to_render.as_millis()
-        );
         if matches!(self.debug, Debug::On) {
             let mut debug = Group::new();
             debug.add(self.as_rect());
             self.groups.push(debug);
         }
+        if matches!(self.optimization, Optimization::On) {
+            self.path_order = self
+                .groups
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, g)| !g.elements.is_empty())
+                .map(|(id, g)| (id, g.optimize_path_order()))
+                .collect();
+        }
         self.doc = render_svg(self);
+        print!("{}", self.stats(&PlotterSpeed::default()));
         self
     }
 
@@ -140,6 +203,31 @@ impl Sketch {
         Ok(())
     }
 
+    /// Save the sketch as G-code, ready to be streamed to a GRBL-based plotter
+    pub fn save_gcode(&self, path: &str, config: &GcodeConfig) -> Result<()> {
+        std::fs::write(path, render_gcode(self, config)).context("Cannot save G-code file")?;
+        println!("Output written in '{path}'");
+        Ok(())
+    }
+
+    /// Save the sketch as HPGL, for pen plotters like the HP 7475A or the Roland DXY series
+    pub fn save_hpgl(&self, path: &str) -> Result<()> {
+        std::fs::write(path, render_hpgl(self)).context("Cannot save HPGL file")?;
+        println!("Output written in '{path}'");
+        Ok(())
+    }
+
+    /// Save a raster preview of the sketch as PNG, at `dpi` dots per inch
+    pub fn save_png(&self, path: &str, dpi: f64) -> Result<()> {
+        render_raster(self, dpi).save_png(path)?;
+        println!("Output written in '{path}'");
+        Ok(())
+    }
+
+    /// Save the sketch in the samples directory, naming it after the binary.
+    ///
+    /// When the `PLT_PNG_DPI` environment variable is set, a PNG preview
+    /// at that resolution is saved alongside the SVG.
     pub fn save_default(&self) -> Result<()> {
         let bin_name = std::env::current_exe()?
             .file_name()
@@ -150,6 +238,10 @@ impl Sketch {
             .unwrap();
         let path = format!("samples/{bin_name}.svg");
         self.save_to(&path)?;
+        if let Ok(dpi) = std::env::var("PLT_PNG_DPI") {
+            let dpi = dpi.parse().context("Invalid PLT_PNG_DPI value")?;
+            self.save_png(&format!("samples/{bin_name}.png"), dpi)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,218 @@
+//! Plot statistics and time estimates
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::group::Group;
+use crate::uom::Uom;
+use crate::vec2::Vec2;
+
+// Maximum deviation, in millimeters, when approximating curves
+const TOLERANCE: f64 = 0.1;
+// The pen stops at corners sharper than this
+const CORNER_ANGLE: f64 = 30.;
+
+/// How fast a plotter moves. All values are in millimeters and seconds.
+#[derive(Clone)]
+pub struct PlotterSpeed {
+    /// Maximum speed while drawing.
+    pub pen_down_speed: f64,
+    /// Maximum speed while travelling with the pen up.
+    pub pen_up_speed: f64,
+    /// Acceleration and deceleration.
+    pub acceleration: f64,
+    /// Time needed to lift or lower the pen.
+    pub pen_lift_time: f64,
+}
+
+impl PlotterSpeed {
+    pub fn new(
+        pen_down_speed: f64,
+        pen_up_speed: f64,
+        acceleration: f64,
+        pen_lift_time: f64,
+    ) -> Self {
+        Self {
+            pen_down_speed,
+            pen_up_speed,
+            acceleration,
+            pen_lift_time,
+        }
+    }
+
+    /// Time to move along a straight line of `distance` at most at `speed`,
+    /// starting and ending still.
+    fn move_time(&self, distance: f64, speed: f64) -> f64 {
+        if distance >= speed * speed / self.acceleration {
+            distance / speed + speed / self.acceleration
+        } else {
+            2. * (distance / self.acceleration).sqrt()
+        }
+    }
+}
+
+impl Default for PlotterSpeed {
+    /// Speeds in the ballpark of an AxiDraw with default settings.
+    fn default() -> Self {
+        Self::new(25., 75., 200., 0.15)
+    }
+}
+
+/// Statistics about a single group. Distances are in the `Uom` of the sketch.
+#[derive(Clone, Default)]
+pub struct GroupStats {
+    /// Distance travelled with the pen down.
+    pub pen_down: f64,
+    /// Distance travelled with the pen up, from the origin to the last path.
+    pub pen_up: f64,
+    /// Number of times the pen is lifted.
+    pub pen_lifts: usize,
+    /// Number of paths.
+    pub paths: usize,
+    /// Estimated time to plot the group.
+    pub duration: Duration,
+}
+
+impl GroupStats {
+    /// Compute the statistics of `group`, whose coordinates are expressed in `uom`.
+    pub fn new(group: &Group, uom: Uom, speed: &PlotterSpeed) -> Self {
+        let tolerance = Uom::convert_scalar(TOLERANCE, Uom::Mm, uom);
+        let to_mm = |d: f64| Uom::convert_scalar(d, uom, Uom::Mm);
+        let mut stats = GroupStats::default();
+        let mut seconds = 0.;
+        let mut position: Option<Vec2> = None;
+        for path in group.elements.iter().flat_map(|e| e.flatten(tolerance)) {
+            let Some(&start) = path.points.first() else {
+                continue;
+            };
+            stats.paths += 1;
+            if position.is_none_or(|p| p.distance(start) > 1e-9) {
+                let travel = position.unwrap_or(Vec2::ZERO).distance(start);
+                stats.pen_up += travel;
+                stats.pen_lifts += 1;
+                seconds += speed.move_time(to_mm(travel), speed.pen_up_speed);
+                seconds += 2. * speed.pen_lift_time;
+            }
+            // Split the path where the pen has to stop to turn
+            let mut stroke = 0.;
+            for (i, pair) in path.points.windows(2).enumerate() {
+                let length = pair[0].distance(pair[1]);
+                stats.pen_down += length;
+                stroke += length;
+                let corner = path.points.get(i + 2).is_some_and(|next| {
+                    let turn = pair[0].angle_between(pair[1]) - pair[1].angle_between(*next);
+                    let turn = turn.to_degrees().abs();
+                    f64::min(turn, 360. - turn) > CORNER_ANGLE
+                });
+                if corner {
+                    seconds += speed.move_time(to_mm(stroke), speed.pen_down_speed);
+                    stroke = 0.;
+                }
+            }
+            seconds += speed.move_time(to_mm(stroke), speed.pen_down_speed);
+            position = path.points.last().copied();
+        }
+        stats.duration = Duration::from_secs_f64(seconds);
+        stats
+    }
+}
+
+/// Statistics about a whole sketch, one entry per non-empty group.
+pub struct SketchStats {
+    /// The group index and its statistics.
+    pub groups: Vec<(usize, GroupStats)>,
+}
+
+impl SketchStats {
+    /// Return the statistics summed over all the groups.
+    pub fn total(&self) -> GroupStats {
+        let mut total = GroupStats::default();
+        for (_, g) in &self.groups {
+            total.pen_down += g.pen_down;
+            total.pen_up += g.pen_up;
+            total.pen_lifts += g.pen_lifts;
+            total.paths += g.paths;
+            total.duration += g.duration;
+        }
+        total
+    }
+}
+
+impl fmt::Display for SketchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn row(f: &mut fmt::Formatter<'_>, label: &str, g: &GroupStats) -> fmt::Result {
+            let secs = g.duration.as_secs();
+            writeln!(
+                f,
+                "{:<7} {:>8} {:>8} {:>12.1} {:>12.1} {:>4}h{:02}m{:02}s",
+                label,
+                g.paths,
+                g.pen_lifts,
+                g.pen_down,
+                g.pen_up,
+                secs / 3600,
+                secs % 3600 / 60,
+                secs % 60
+            )
+        }
+        writeln!(
+            f,
+            "{:<7} {:>8} {:>8} {:>12} {:>12} {:>10}",
+            "Group", "Paths", "Lifts", "Pen down", "Pen up", "Time"
+        )?;
+        for (id, g) in &self.groups {
+            row(f, &format!("{}", id + 1), g)?;
+        }
+        row(f, "Total", &self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::stats::{GroupStats, PlotterSpeed};
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn distances_and_lifts() {
+        let mut group = Group::new();
+        group.add(LineString::line(Vec2::new(0., 10.), Vec2::new(10., 10.)));
+        group.add(LineString::line(Vec2::new(10., 10.), Vec2::new(10., 20.)));
+        group.add(Rect::new(Vec2::new(10., 30.), 5., 5.));
+        let stats = GroupStats::new(&group, Uom::Mm, &PlotterSpeed::default());
+        assert_eq!(stats.paths, 3);
+        assert_eq!(stats.pen_lifts, 2);
+        assert_relative_eq!(stats.pen_down, 40.);
+        assert_relative_eq!(stats.pen_up, 20.);
+    }
+
+    #[test]
+    fn duration() {
+        let mut group = Group::new();
+        group.add(LineString::line(Vec2::new(0., 0.), Vec2::new(100., 0.)));
+        let speed = PlotterSpeed::new(10., 20., 100., 0.5);
+        let stats = GroupStats::new(&group, Uom::Mm, &speed);
+        // Two pen moves (up and down), then 100mm at 10mm/s plus acceleration
+        assert_relative_eq!(stats.duration.as_secs_f64(), 1. + 10.1, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn corners_slow_down() {
+        let mut straight = Group::new();
+        straight.add(LineString::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(50., 0.),
+            Vec2::new(100., 0.),
+        ]));
+        let mut bent = Group::new();
+        bent.add(LineString::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(50., 0.),
+            Vec2::new(50., 50.),
+        ]));
+        let speed = PlotterSpeed::default();
+        let straight = GroupStats::new(&straight, Uom::Mm, &speed);
+        let bent = GroupStats::new(&bent, Uom::Mm, &speed);
+        assert!(bent.duration > straight.duration);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::pen::Pen;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Style {
     pub stroke: String,
     pub stroke_width: String,
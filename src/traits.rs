@@ -1,21 +1,31 @@
+pub mod boolean;
+pub mod hatch;
+pub mod offset;
 pub mod packing;
 
 use std::f64::consts::TAU;
 
 use crate::angle::Angle;
+use crate::field::Scalar2;
 use crate::prelude::Pen;
+use crate::shapes::arc::Arc;
+use crate::shapes::bezier::Bezier;
 use crate::shapes::circle::Circle;
-use crate::shapes::edge::Edge;
+use crate::shapes::ellipse::Ellipse;
 use crate::shapes::hexagon::Hexagon;
 use crate::shapes::linestring::LineString;
 use crate::shapes::polygon::Polygon;
 use crate::shapes::rectangle::Rect;
 use crate::shapes::triangle::Triangle;
 use crate::shapes::Text;
+use crate::traits::hatch::{Curve, DensityHatch, SpiralHatch};
+use crate::traits::offset::{signed_area, JoinStyle, Offset};
 use crate::vec2::Vec2;
 use crate::Shape;
 use geo::algorithm::Rotate as GeoRotate;
 use geo::coord;
+use geo::Area;
+use geo::Centroid as GeoCentroid;
 use geo::Contains as GeoContains;
 use geo::Coord;
 use geo::CoordsIter;
@@ -27,7 +37,8 @@ pub trait ScalePerc {
 }
 
 pub trait ScaleDist {
-    fn scale_dist(&self, distance: f64) -> Self;
+    type Output;
+    fn scale_dist(&self, distance: f64) -> Self::Output;
 }
 
 pub trait Clip {
@@ -72,6 +83,11 @@ pub trait ToGeoLineString {
     fn to_geo_multilinestring(&self) -> geo::MultiLineString;
 }
 
+/// Conversion to a `geo::Polygon`, holes included.
+pub trait ToGeoPolygon {
+    fn to_geo_polygon(&self) -> geo::Polygon;
+}
+
 pub trait ToShape {
     fn to_shape(&self) -> Shape;
 }
@@ -88,9 +104,31 @@ pub trait Lerp {
 
 #[derive(Clone)]
 pub enum HatchFillStrategy {
+    /// Horizontal lines, touching each other
     HorizontalLines,
-}
-
+    /// Rings following the outline, from the outside in, touching each other
+    Concentric,
+    /// Parallel lines going in the direction of `angle`, `spacing` apart,
+    /// or touching each other when `None`
+    Lines { angle: Angle, spacing: Option<f64> },
+    /// Two sets of parallel lines going in the directions of `angles`,
+    /// `spacing` apart, or touching each other when `None`
+    CrossHatch {
+        angles: (Angle, Angle),
+        spacing: Option<f64>,
+    },
+    /// A Hilbert curve clipped to the shape, its stretches touching each other
+    Hilbert,
+    /// A Peano curve clipped to the shape, its stretches touching each other
+    Peano,
+    /// A Gosper curve clipped to the shape, its stretches touching each other
+    Gosper,
+    /// An Archimedean spiral going outwards from the centroid, its turns
+    /// touching each other
+    Spiral,
+}
+
+/// Filling of closed shapes with lines, following a `HatchFillStrategy`.
 pub trait HatchFill {
     fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString>;
 }
@@ -211,6 +249,12 @@ impl ToGeoLineString for LineString {
     }
 }
 
+impl ToGeoPolygon for LineString {
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        geo::Polygon::new(self.to_geo_linestring(), vec![])
+    }
+}
+
 impl ToShape for LineString {
     fn to_shape(&self) -> Shape {
         Shape::LineString(self.clone())
@@ -257,28 +301,153 @@ impl BoundingBox for LineString {
     }
 }
 
+impl ToShape for Bezier {
+    fn to_shape(&self) -> Shape {
+        Shape::Bezier(self.clone())
+    }
+}
+
+impl BoundingBox for Bezier {
+    fn bbox(&self) -> Rect {
+        let (mut min, mut max) = self.curves()[0].bounds();
+        for (cmin, cmax) in self.curves().iter().map(|c| c.bounds()) {
+            min = Vec2::new(min.x.min(cmin.x), min.y.min(cmin.y));
+            max = Vec2::new(max.x.max(cmax.x), max.y.max(cmax.y));
+        }
+        Rect::new(min, max.x - min.x, max.y - min.y)
+    }
+
+    fn bbox_margin(&self, margin: f64) -> Rect {
+        with_margin(self.bbox(), margin)
+    }
+}
+
+/// Grow `bbox` by `margin` on every side.
+fn with_margin(bbox: Rect, margin: f64) -> Rect {
+    Rect::new(
+        bbox.xy - margin,
+        bbox.width + 2. * margin,
+        bbox.height + 2. * margin,
+    )
+}
+
+impl ToShape for Arc {
+    fn to_shape(&self) -> Shape {
+        Shape::Arc(*self)
+    }
+}
+
+impl Centroid for Arc {
+    /// Return the center of mass of the arc line.
+    fn centroid(&self) -> Vec2 {
+        let half = self.sweep() / 2.;
+        if half == 0. {
+            return self.start_point();
+        }
+        let middle = self.start + Angle::from_radians(half);
+        self.center + Vec2::from_polar(middle, self.radius * half.sin() / half)
+    }
+}
+
+impl Rotate for Arc {
+    fn rotate(&self, angle: Angle) -> Self {
+        let centroid = self.centroid();
+        Arc::new(
+            self.center.rotate_around(centroid, angle),
+            self.radius,
+            self.start + angle,
+            self.end + angle,
+            self.direction,
+        )
+    }
+}
+
+impl Translate for Arc {
+    fn translate(&self, displacement: Vec2) -> Self {
+        Arc::new(
+            self.center + displacement,
+            self.radius,
+            self.start,
+            self.end,
+            self.direction,
+        )
+    }
+}
+
+impl BoundingBox for Arc {
+    fn bbox(&self) -> Rect {
+        let (start, end) = (self.start_point(), self.end_point());
+        let mut min = Vec2::new(start.x.min(end.x), start.y.min(end.y));
+        let mut max = Vec2::new(start.x.max(end.x), start.y.max(end.y));
+        for degrees in [0., 90., 180., 270.] {
+            if self.contains_angle(Angle::from_degrees(degrees)) {
+                let p = self.point_at(Angle::from_degrees(degrees));
+                min = Vec2::new(min.x.min(p.x), min.y.min(p.y));
+                max = Vec2::new(max.x.max(p.x), max.y.max(p.y));
+            }
+        }
+        Rect::new(min, max.x - min.x, max.y - min.y)
+    }
+
+    fn bbox_margin(&self, margin: f64) -> Rect {
+        with_margin(self.bbox(), margin)
+    }
+}
+
+impl ToShape for Ellipse {
+    fn to_shape(&self) -> Shape {
+        Shape::Ellipse(*self)
+    }
+}
+
+impl Centroid for Ellipse {
+    fn centroid(&self) -> Vec2 {
+        self.center
+    }
+}
+
+impl Rotate for Ellipse {
+    fn rotate(&self, angle: Angle) -> Self {
+        Ellipse::new(self.center, self.rx, self.ry, self.rotation + angle)
+    }
+}
+
+impl Translate for Ellipse {
+    fn translate(&self, displacement: Vec2) -> Self {
+        Ellipse::new(self.center + displacement, self.rx, self.ry, self.rotation)
+    }
+}
+
+impl BoundingBox for Ellipse {
+    fn bbox(&self) -> Rect {
+        let (sin, cos) = (self.rotation.sin(), self.rotation.cos());
+        let half = Vec2::new(
+            (self.rx * self.rx * cos * cos + self.ry * self.ry * sin * sin).sqrt(),
+            (self.rx * self.rx * sin * sin + self.ry * self.ry * cos * cos).sqrt(),
+        );
+        Rect::new(self.center - half, half.x * 2., half.y * 2.)
+    }
+
+    fn bbox_margin(&self, margin: f64) -> Rect {
+        with_margin(self.bbox(), margin)
+    }
+}
+
 impl Rotate for Polygon {
     // TODO: add direction (clockwise, anti-clockwise) of rotation
     // TODO: implement from scratch?
     fn rotate(&self, angle: Angle) -> Self {
-        let poly: geo::Polygon = geo::Polygon::new(
-            geo::LineString::new(
-                self.points
-                    .clone()
-                    .iter()
-                    .map(|v| coord! {x: v.x, y: v.y})
-                    .collect::<Vec<Coord>>(),
-            ),
-            vec![],
-        );
         let degrees = angle.to_radians() * 180.0 / TAU;
-        let poly = poly.rotate_around_centroid(degrees);
-        Polygon::new(
-            poly.exterior()
-                .points()
+        let poly = self.to_geo_polygon().rotate_around_centroid(degrees);
+        let ring = |ring: &geo::LineString| {
+            ring.points()
                 .map(|p| p.coords_iter().nth(0).unwrap())
                 .map(|p| Vec2 { x: p.x, y: p.y })
-                .collect::<Vec<Vec2>>(),
+                .collect::<Vec<Vec2>>()
+        };
+        Polygon::with_interiors(
+            ring(poly.exterior()),
+            poly.interiors().iter().map(ring).collect(),
         )
     }
 }
@@ -294,62 +463,105 @@ impl ToGeoLineString for Polygon {
         )
     }
 
+    /// Return all the rings, closed.
     fn to_geo_multilinestring(&self) -> geo::MultiLineString {
-        geo::MultiLineString::new(vec![self.to_geo_linestring()])
+        geo::MultiLineString::new(
+            self.rings()
+                .iter()
+                .map(|ring| ring.to_geo_linestring())
+                .collect(),
+        )
+    }
+}
+
+impl ToGeoPolygon for Polygon {
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        let ring = |points: &[Vec2]| {
+            geo::LineString::new(
+                points
+                    .iter()
+                    .map(|v| coord! {x: v.x, y: v.y})
+                    .collect::<Vec<Coord>>(),
+            )
+        };
+        geo::Polygon::new(
+            ring(&self.points),
+            self.interiors.iter().map(|i| ring(i)).collect(),
+        )
     }
 }
 
 impl Upsample for Polygon {
     fn upsample(&self, factor: u64) -> Self {
-        // TODO: add wrap bool argument like Chaikin
-        let mut points = self.points.clone();
-        (0..factor).for_each(|_| {
-            let mut upsampled = vec![];
-            for i in 1..points.len() {
-                upsampled.push(points[i - 1]);
-                let middle_point = Vec2 {
-                    x: (points[i - 1].x + points[i].x) * 0.5,
-                    y: (points[i - 1].y + points[i].y) * 0.5,
-                };
-                upsampled.push(middle_point);
-            }
-            upsampled.push(Vec2 {
-                x: (points[0].x + points[points.len() - 1].x) * 0.5,
-                y: (points[0].y + points[points.len() - 1].y) * 0.5,
-            });
-            points.clone_from(&upsampled);
-        });
-        Polygon::new(points)
+        Polygon::with_interiors(
+            upsample_ring(&self.points, factor),
+            self.interiors
+                .iter()
+                .map(|ring| upsample_ring(ring, factor))
+                .collect(),
+        )
     }
 }
 
+fn upsample_ring(points: &[Vec2], factor: u64) -> Vec<Vec2> {
+    // TODO: add wrap bool argument like Chaikin
+    let mut points = points.to_vec();
+    (0..factor).for_each(|_| {
+        let mut upsampled = vec![];
+        for i in 1..points.len() {
+            upsampled.push(points[i - 1]);
+            let middle_point = Vec2 {
+                x: (points[i - 1].x + points[i].x) * 0.5,
+                y: (points[i - 1].y + points[i].y) * 0.5,
+            };
+            upsampled.push(middle_point);
+        }
+        upsampled.push(Vec2 {
+            x: (points[0].x + points[points.len() - 1].x) * 0.5,
+            y: (points[0].y + points[points.len() - 1].y) * 0.5,
+        });
+        points.clone_from(&upsampled);
+    });
+    points
+}
+
 impl Chaikin for Polygon {
     fn chaikin(&self, iterations: u64, closed: bool) -> Self {
-        let mut points = self.points.clone();
-        (0..iterations).for_each(|_| {
-            let mut smoothed = vec![];
-            for i in 1..points.len() {
-                smoothed.push(Vec2 {
-                    x: points[i - 1].x * 0.75 + points[i].x * 0.25,
-                    y: points[i - 1].y * 0.75 + points[i].y * 0.25,
-                });
-                smoothed.push(Vec2 {
-                    x: points[i - 1].x * 0.25 + points[i].x * 0.75,
-                    y: points[i - 1].y * 0.25 + points[i].y * 0.75,
-                });
-            }
-            if closed {
-                smoothed.push(Vec2 {
-                    x: points[points.len() - 1].x * 0.75 + points[1].x * 0.25,
-                    y: points[points.len() - 1].y * 0.75 + points[1].y * 0.25,
-                });
-            }
-            points.clone_from(&smoothed);
-        });
-        Polygon::new(points)
+        Polygon::with_interiors(
+            chaikin_ring(&self.points, iterations, closed),
+            self.interiors
+                .iter()
+                .map(|ring| chaikin_ring(ring, iterations, closed))
+                .collect(),
+        )
     }
 }
 
+fn chaikin_ring(points: &[Vec2], iterations: u64, closed: bool) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    (0..iterations).for_each(|_| {
+        let mut smoothed = vec![];
+        for i in 1..points.len() {
+            smoothed.push(Vec2 {
+                x: points[i - 1].x * 0.75 + points[i].x * 0.25,
+                y: points[i - 1].y * 0.75 + points[i].y * 0.25,
+            });
+            smoothed.push(Vec2 {
+                x: points[i - 1].x * 0.25 + points[i].x * 0.75,
+                y: points[i - 1].y * 0.25 + points[i].y * 0.75,
+            });
+        }
+        if closed {
+            smoothed.push(Vec2 {
+                x: points[points.len() - 1].x * 0.75 + points[1].x * 0.25,
+                y: points[points.len() - 1].y * 0.75 + points[1].y * 0.25,
+            });
+        }
+        points.clone_from(&smoothed);
+    });
+    points
+}
+
 impl ToShape for Polygon {
     fn to_shape(&self) -> Shape {
         Shape::Polygon(self.clone())
@@ -357,24 +569,23 @@ impl ToShape for Polygon {
 }
 
 impl Centroid for Polygon {
+    /// Return the center of mass of the area, holes excluded, or the average
+    /// of the points when there is no area.
     fn centroid(&self) -> Vec2 {
-        // TODO: we must prevent division by zero
-        let mut xsum: f64 = 0.;
-        let mut ysum: f64 = 0.;
-        self.points.iter().for_each(|p| {
-            xsum += p.x;
-            ysum += p.y;
-        });
-        Vec2 {
-            x: xsum / self.points.len() as f64,
-            y: ysum / self.points.len() as f64,
+        let poly = self.to_geo_polygon();
+        if poly.unsigned_area() > 0. {
+            if let Some(c) = GeoCentroid::centroid(&poly) {
+                return Vec2::new(c.x(), c.y());
+            }
         }
+        let sum = self.points.iter().copied().sum::<Vec2>();
+        sum / self.points.len() as f64
     }
 }
 
 impl Sample for Polygon {
     fn sample_uniform(&self, rng: &mut StdRng, n: u64) -> Vec<Vec2> {
-        let poly = geo::Polygon::new(self.to_geo_linestring(), vec![]);
+        let poly = self.to_geo_polygon();
         let miny = self.points.iter().map(|p| p.y as usize).min().unwrap();
         let maxy = self.points.iter().map(|p| p.y as usize).max().unwrap();
         let minx = self.points.iter().map(|p| p.x as usize).min().unwrap();
@@ -395,62 +606,44 @@ impl Sample for Polygon {
 
 impl Contains for Polygon {
     fn contains<T: Centroid>(&self, shape: &T) -> bool {
-        let poly = geo::Polygon::new(self.to_geo_linestring(), vec![]);
+        let poly = self.to_geo_polygon();
         let other_centroid = shape.centroid();
         poly.contains(&coord! { x: other_centroid.x, y: other_centroid.y })
     }
 }
 
 impl ScaleDist for Polygon {
-    fn scale_dist(&self, distance: f64) -> Polygon {
-        // This is a very raw and fragile adaptation of this code:
-        // https://codepen.io/HansMuller/pen/AgLWaz
-        let edges = self.edges();
-
-        fn inward_normal(p1: Vec2, p2: Vec2) -> Vec2 {
-            let dx = p2.x - p1.x;
-            let dy = p2.y - p1.y;
-            let edge_length = f64::sqrt(dx * dx + dy * dy);
-            Vec2::new(-dy / edge_length, dx / edge_length)
-        }
-
-        let mut offset_edges: Vec<Edge> = vec![];
-        for edge in &edges {
-            let normal = inward_normal(edge.v1, edge.v2);
-            let dx = normal.x * distance;
-            let dy = normal.y * distance;
-            let offset_edge = Edge {
-                v1: Vec2::new(edge.v1.x + dx, edge.v1.y + dy),
-                v2: Vec2::new(edge.v2.x + dx, edge.v2.y + dy),
-            };
-            offset_edges.push(offset_edge)
-        }
-
-        fn edges_intersection(edge1: Edge, edge2: Edge) -> Vec2 {
-            let den = (edge2.v2.y - edge2.v1.y) * (edge1.v2.x - edge1.v1.x)
-                - (edge2.v2.x - edge2.v1.x) * (edge1.v2.y - edge1.v1.y);
-            if den == 0. {
-                panic!(); // lines are parallel or conincident
-            }
-
-            let ua = ((edge2.v2.x - edge2.v1.x) * (edge1.v1.y - edge2.v1.y)
-                - (edge2.v2.y - edge2.v1.y) * (edge1.v1.x - edge2.v1.x))
-                / den;
-
-            Vec2::new(
-                edge1.v1.x + ua * (edge1.v2.x - edge1.v1.x),
-                edge1.v1.y + ua * (edge1.v2.y - edge1.v1.y),
-            )
+    type Output = Option<Polygon>;
+
+    /// Move the edges by `distance` along their normal `(-dy, dx)`, so that
+    /// a polygon wound clockwise on the page shrinks and one wound the other
+    /// way grows, holes following the exterior. The result keeps the winding
+    /// and starts next to the first point. When shrinking splits the polygon
+    /// only the largest piece is kept, and when it vanishes `None` is
+    /// returned: use `Offset` to get all of them.
+    fn scale_dist(&self, distance: f64) -> Option<Polygon> {
+        let clockwise = signed_area(&self.points) > 0.;
+        let distance = if clockwise { -distance } else { distance };
+        let mut polygon = self
+            .offset(distance, JoinStyle::default())
+            .into_iter()
+            .max_by(|a, b| {
+                let area = |p: &Polygon| p.to_geo_polygon().unsigned_area();
+                area(a).total_cmp(&area(b))
+            })?;
+        if !clockwise {
+            polygon.points.reverse();
+            polygon.interiors.iter_mut().for_each(|r| r.reverse());
         }
-
-        let mut vertices: Vec<Vec2> = vec![];
-        for i in 0..offset_edges.len() {
-            let this_edge = offset_edges[i];
-            let prev_edge = offset_edges[(i + offset_edges.len() - 1) % offset_edges.len()];
-            let vertex = edges_intersection(prev_edge, this_edge);
-            vertices.push(vertex);
-        }
-        Polygon::new(vertices)
+        let start = polygon
+            .points
+            .iter()
+            .map(|p| p.distance(self.points[0]))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(i, _)| i);
+        polygon.points.rotate_left(start);
+        Some(polygon)
     }
 }
 
@@ -493,6 +686,12 @@ impl Centroid for Rect {
     }
 }
 
+impl ToGeoPolygon for Rect {
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        self.to_polygon().to_geo_polygon()
+    }
+}
+
 impl ToShape for Rect {
     fn to_shape(&self) -> Shape {
         Shape::Rectangle(self.clone())
@@ -525,6 +724,15 @@ impl Contains for Circle {
     }
 }
 
+impl ToGeoPolygon for Circle {
+    /// Approximate the circle with chords deviating from it by at most a
+    /// thousandth of the radius.
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        let points = self.points_for_tolerance(self.radius / 1000.);
+        self.to_polygon(points).to_geo_polygon()
+    }
+}
+
 impl ScalePerc for Circle {
     fn scale_perc(&self, percentage: f64) -> Circle {
         Circle::new(self.center, self.radius * percentage)
@@ -617,6 +825,12 @@ impl ToShape for Triangle {
     }
 }
 
+impl ToGeoPolygon for Triangle {
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        self.to_polygon().to_geo_polygon()
+    }
+}
+
 impl ToShape for Text {
     fn to_shape(&self) -> Shape {
         Shape::Text(self.clone())
@@ -629,6 +843,12 @@ impl ToShape for Hexagon {
     }
 }
 
+impl ToGeoPolygon for Hexagon {
+    fn to_geo_polygon(&self) -> geo::Polygon {
+        self.to_polygon().to_geo_polygon()
+    }
+}
+
 impl ScalePerc for Hexagon {
     fn scale_perc(&self, percentage: f64) -> Hexagon {
         Hexagon::new(self.center, self.side * percentage, self.theta)
@@ -636,32 +856,82 @@ impl ScalePerc for Hexagon {
 }
 
 impl ScaleDist for Hexagon {
+    type Output = Self;
+
     fn scale_dist(&self, distance: f64) -> Self {
         Hexagon::new(self.center, self.side + distance, self.theta)
     }
 }
 
+/// Filling of closed shapes with lines whose density follows a field.
+pub trait DensityFill {
+    fn density_fill<F: Scalar2>(&self, field: &F, hatch: &DensityHatch) -> Vec<LineString>;
+
+    /// Fill with a single spiral, wobbling more where `field` is darker.
+    fn density_spiral<F: Scalar2>(&self, field: &F, hatch: &SpiralHatch) -> Vec<LineString>;
+}
+
+impl<S: ToGeoPolygon> DensityFill for S {
+    fn density_fill<F: Scalar2>(&self, field: &F, hatch: &DensityHatch) -> Vec<LineString> {
+        hatch::density(self, field, hatch)
+    }
+
+    fn density_spiral<F: Scalar2>(&self, field: &F, hatch: &SpiralHatch) -> Vec<LineString> {
+        hatch::density_spiral(self, field, hatch)
+    }
+}
+
+/// Fill any closed shape with the given hatching strategy.
+fn hatch_fill<T: ToGeoPolygon>(
+    shape: &T,
+    pen: &Pen,
+    strategy: HatchFillStrategy,
+) -> Vec<LineString> {
+    match strategy {
+        HatchFillStrategy::HorizontalLines => hatch::lines(shape, Angle::zero(), pen.thickness),
+        HatchFillStrategy::Concentric => hatch::concentric(shape, pen),
+        HatchFillStrategy::Lines { angle, spacing } => {
+            hatch::lines(shape, angle, spacing.unwrap_or(pen.thickness))
+        }
+        HatchFillStrategy::CrossHatch { angles, spacing } => {
+            let spacing = spacing.unwrap_or(pen.thickness);
+            let mut lines = hatch::lines(shape, angles.0, spacing);
+            lines.extend(hatch::lines(shape, angles.1, spacing));
+            lines
+        }
+        HatchFillStrategy::Hilbert => hatch::space_filling(shape, Curve::Hilbert, pen.thickness),
+        HatchFillStrategy::Peano => hatch::space_filling(shape, Curve::Peano, pen.thickness),
+        HatchFillStrategy::Gosper => hatch::space_filling(shape, Curve::Gosper, pen.thickness),
+        HatchFillStrategy::Spiral => hatch::spiral(shape, pen.thickness),
+    }
+}
+
+impl HatchFill for Polygon {
+    fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString> {
+        hatch_fill(self, pen, strategy)
+    }
+}
+
+impl HatchFill for Rect {
+    fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString> {
+        hatch_fill(self, pen, strategy)
+    }
+}
+
+impl HatchFill for Circle {
+    fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString> {
+        hatch_fill(self, pen, strategy)
+    }
+}
+
+impl HatchFill for Triangle {
+    fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString> {
+        hatch_fill(self, pen, strategy)
+    }
+}
+
 impl HatchFill for Hexagon {
     fn hatch_fill(&self, pen: &Pen, strategy: HatchFillStrategy) -> Vec<LineString> {
-        let mut lines = vec![];
-        match strategy {
-            HatchFillStrategy::HorizontalLines => {
-                let square = Rect::square_with_center(self.center, self.side * 2.);
-                let mut y = self.center.y - self.side;
-                let x = square.centroid().x - self.side + pen.thickness / 2.;
-                while y < self.center.y + self.side - pen.thickness / 2. {
-                    lines.push(LineString::line(
-                        Vec2::new(x, y),
-                        Vec2::new(x + self.side * 2. + pen.thickness / 2., y),
-                    ));
-                    y += pen.thickness;
-                }
-                lines = lines
-                    .iter()
-                    .flat_map(|l| l.clip(&self.to_polygon(), false))
-                    .collect::<Vec<LineString>>();
-            }
-        }
-        lines
+        hatch_fill(self, pen, strategy)
     }
 }
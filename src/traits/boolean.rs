@@ -0,0 +1,142 @@
+use crate::shapes::polygon::Polygon;
+use crate::traits::ToGeoPolygon;
+use geo::algorithm::bool_ops::BooleanOps;
+use geo::Area;
+use geo::LineString as GeoLineString;
+use geo::MultiPolygon as GeoMultiPolygon;
+use geo::Polygon as GeoPolygon;
+
+/// Boolean operations between closed shapes.
+///
+/// Every operation returns the resulting polygons, holes included: the
+/// result may be empty, e.g. the intersection of two disjoint shapes, or
+/// made of several pieces, e.g. the difference of a rectangle and a
+/// band crossing it.
+pub trait Boolean {
+    /// Return the area covered by `self` or `other`.
+    fn union<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon>;
+    /// Return the area covered by both `self` and `other`.
+    fn intersection<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon>;
+    /// Return the area covered by `self` but not by `other`.
+    fn difference<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon>;
+    /// Return the area covered by exactly one of `self` and `other`.
+    fn xor<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon>;
+}
+
+impl<S: ToGeoPolygon> Boolean for S {
+    fn union<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon> {
+        from_geo(self.to_geo_polygon().union(&other.to_geo_polygon()))
+    }
+
+    fn intersection<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon> {
+        from_geo(self.to_geo_polygon().intersection(&other.to_geo_polygon()))
+    }
+
+    fn difference<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon> {
+        from_geo(self.to_geo_polygon().difference(&other.to_geo_polygon()))
+    }
+
+    fn xor<T: ToGeoPolygon>(&self, other: &T) -> Vec<Polygon> {
+        from_geo(self.to_geo_polygon().xor(&other.to_geo_polygon()))
+    }
+}
+
+/// Return the union of all `shapes`, e.g. the silhouette of a tiling.
+pub fn union_all<T: ToGeoPolygon>(shapes: &[T]) -> Vec<Polygon> {
+    from_geo(union_geo(
+        shapes
+            .iter()
+            .map(|shape| GeoMultiPolygon::new(vec![shape.to_geo_polygon()]))
+            .collect(),
+    ))
+}
+
+/// Merge `pieces` pairwise, so that each union involves shapes of similar size.
+fn union_geo(mut pieces: Vec<GeoMultiPolygon>) -> GeoMultiPolygon {
+    while pieces.len() > 1 {
+        pieces = pieces
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => a.union(b),
+                _ => pair[0].clone(),
+            })
+            .collect();
+    }
+    pieces.pop().unwrap_or_else(|| GeoMultiPolygon::new(vec![]))
+}
+
+// Fraction of the total area under which a ring is considered a sliver
+const SLIVER_AREA: f64 = 1e-9;
+
+// Nearly coincident edges, e.g. the shared sides of a tiling, leave
+// slivers behind: drop the rings enclosing a negligible area.
+fn from_geo(mpoly: GeoMultiPolygon) -> Vec<Polygon> {
+    let min_area = mpoly.unsigned_area() * SLIVER_AREA;
+    let large =
+        |ring: &&GeoLineString| GeoPolygon::new((*ring).clone(), vec![]).unsigned_area() > min_area;
+    mpoly
+        .iter()
+        .filter(|poly| large(&poly.exterior()))
+        .map(|poly| {
+            let interiors = poly.interiors().iter().filter(large).cloned().collect();
+            Polygon::from(&GeoPolygon::new(poly.exterior().clone(), interiors))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::traits::boolean::union_all;
+    use crate::traits::ToGeoPolygon;
+    use approx::assert_relative_eq;
+    use geo::Area;
+
+    fn area(polygons: &[Polygon]) -> f64 {
+        polygons
+            .iter()
+            .map(|p| p.to_geo_polygon().unsigned_area())
+            .sum()
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let a = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let b = Rect::new(Vec2::new(5., 5.), 10., 10.);
+        assert_relative_eq!(area(&a.union(&b)), 175.);
+        assert_relative_eq!(area(&a.intersection(&b)), 25.);
+        assert_relative_eq!(area(&a.difference(&b)), 75.);
+        assert_relative_eq!(area(&a.xor(&b)), 150.);
+        assert_eq!(a.union(&b).len(), 1);
+        assert_eq!(a.xor(&b).len(), 2);
+    }
+
+    #[test]
+    fn difference_makes_holes_and_pieces() {
+        let square = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let hole = Triangle::new(Vec2::new(2., 2.), Vec2::new(8., 2.), Vec2::new(5., 8.));
+        let framed = square.difference(&hole);
+        assert_eq!(framed.len(), 1);
+        assert_eq!(framed[0].interiors.len(), 1);
+        assert!(!framed[0].contains(&Vec2::new(5., 4.)));
+
+        let band = Rect::new(Vec2::new(-1., 4.), 12., 2.);
+        assert_eq!(square.difference(&band).len(), 2);
+        let far = Circle::new(Vec2::new(50., 50.), 5.);
+        assert!(square.intersection(&far).is_empty());
+    }
+
+    #[test]
+    fn hexagon_silhouette() {
+        let hexagons = Hexagon::spiral(Vec2::ZERO, 10., Angle::zero(), 7);
+        let merged = union_all(&hexagons);
+        assert_eq!(merged.len(), 1);
+        assert!(merged[0].interiors.is_empty());
+        let single = Hexagon::new(Vec2::ZERO, 10., Angle::zero());
+        assert_relative_eq!(
+            area(&merged),
+            7. * area(&[single.to_polygon()]),
+            epsilon = 1e-6
+        );
+    }
+}
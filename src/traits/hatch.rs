@@ -0,0 +1,847 @@
+use crate::angle::Angle;
+use crate::field::Scalar2;
+use crate::pen::Pen;
+use crate::shapes::linestring::LineString;
+use crate::shapes::polygon::Polygon;
+use crate::traits::offset::{JoinStyle, Offset};
+use crate::traits::{Lerp, ToGeoPolygon};
+use crate::vec2::Vec2;
+use geo::{BoundingRect, Centroid, EuclideanDistance};
+use rstar::RTree;
+use std::f64::consts::TAU;
+
+/// Fill `shape` with parallel lines going in the direction of `angle`,
+/// `spacing` apart. Consecutive lines are joined at alternate ends, as long
+/// as the joining segment stays inside the shape, so that a convex shape
+/// is drawn as a single zig-zag path.
+pub(crate) fn lines<T: ToGeoPolygon>(shape: &T, angle: Angle, spacing: f64) -> Vec<LineString> {
+    let poly = shape.to_geo_polygon();
+    // The paths being drawn, and the index of the last line they reached
+    let mut chains: Vec<(Vec<Vec2>, usize)> = vec![];
+    for (index, segments) in scanlines(&poly, angle, spacing).into_iter().enumerate() {
+        for (start, end) in segments {
+            // Continue a path that reached the previous line, from the
+            // closest end of the segment
+            let joined = chains
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, last))| *last + 1 == index)
+                .flat_map(|(i, (points, _))| {
+                    let tail = points[points.len() - 1];
+                    [(i, tail, start, end), (i, tail, end, start)]
+                })
+                .filter(|(_, tail, from, _)| connects_inside(&poly, *tail, *from, spacing))
+                .min_by(|a, b| a.1.distance(a.2).total_cmp(&b.1.distance(b.2)));
+            match joined {
+                Some((i, _, from, to)) => {
+                    chains[i].0.extend([from, to]);
+                    chains[i].1 = index;
+                }
+                None => chains.push((vec![start, end], index)),
+            }
+        }
+    }
+    chains
+        .into_iter()
+        .map(|(points, _)| LineString::new(points))
+        .collect()
+}
+
+/// Return the pieces of the parallel lines going in the direction of
+/// `angle`, `spacing` apart, that fall inside `poly`, line by line.
+fn scanlines(poly: &geo::Polygon, angle: Angle, spacing: f64) -> Vec<Vec<(Vec2, Vec2)>> {
+    let outline = Polygon::from(poly);
+    let direction = Vec2::from_polar(angle, 1.);
+    let normal = Vec2::new(-direction.y, direction.x);
+    let along = |p: Vec2, axis: Vec2| p.x * axis.x + p.y * axis.y;
+    let points = outline.rings().into_iter().flat_map(|r| r.points);
+    let (mut dmin, mut dmax, mut nmin, mut nmax) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for p in points {
+        dmin = dmin.min(along(p, direction));
+        dmax = dmax.max(along(p, direction));
+        nmin = nmin.min(along(p, normal));
+        nmax = nmax.max(along(p, normal));
+    }
+    let mut lines = vec![];
+    if spacing <= 0. || dmin > dmax {
+        return lines;
+    }
+    let mut offset = nmin + spacing / 2.;
+    while offset < nmax {
+        let line = LineString::line(
+            normal * offset + direction * (dmin - spacing),
+            normal * offset + direction * (dmax + spacing),
+        );
+        lines.push(
+            line.clip(&outline, false)
+                .iter()
+                .map(|l| (l.points[0], l.points[l.points.len() - 1]))
+                .collect(),
+        );
+        offset += spacing;
+    }
+    lines
+}
+
+/// The brightness sampled from a field, the value `black` mapping to 0 and
+/// `white` to 1.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Brightness {
+    pub black: f64,
+    pub white: f64,
+}
+
+impl Brightness {
+    pub fn new(black: f64, white: f64) -> Self {
+        Self { black, white }
+    }
+
+    /// Return the brightness of `field` at `pos`, between 0 and 1.
+    pub fn sample<F: Scalar2>(&self, field: &F, pos: Vec2) -> f64 {
+        ((field.number2(pos) - self.black) / (self.white - self.black)).clamp(0., 1.)
+    }
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self::new(0., 1.)
+    }
+}
+
+/// The settings of a hatching whose density follows the brightness sampled
+/// from a field: the lines are spaced by `spacing.0` where the field is
+/// darkest, and thinned out by powers of two up to `spacing.1` where it is
+/// lightest.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DensityHatch {
+    /// The direction of the lines
+    pub angle: Angle,
+    /// The spacing of the lines where the field is darkest and lightest
+    pub spacing: (f64, f64),
+    /// The brightness setting the spacing
+    pub brightness: Brightness,
+    /// The further layers of lines, drawn in the direction of the angle
+    /// where the brightness is lower than the threshold
+    pub layers: Vec<(f64, Angle)>,
+}
+
+impl DensityHatch {
+    pub fn new(angle: Angle, dark_spacing: f64, light_spacing: f64) -> Self {
+        Self {
+            angle,
+            spacing: (dark_spacing, light_spacing),
+            brightness: Brightness::default(),
+            layers: vec![],
+        }
+    }
+
+    /// Sample the brightness with the given mapping of the field values.
+    pub fn with_brightness(mut self, brightness: Brightness) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Add a layer of lines in the direction of `angle`, where the
+    /// brightness is lower than `threshold`.
+    pub fn with_layer(mut self, threshold: f64, angle: Angle) -> Self {
+        self.layers.push((threshold, angle));
+        self
+    }
+}
+
+/// Fill `shape` with lines whose density follows `field`.
+pub(crate) fn density<T: ToGeoPolygon, F: Scalar2>(
+    shape: &T,
+    field: &F,
+    hatch: &DensityHatch,
+) -> Vec<LineString> {
+    let poly = shape.to_geo_polygon();
+    let mut lines = density_layer(&poly, field, hatch, hatch.angle, 1.);
+    for (threshold, angle) in &hatch.layers {
+        lines.extend(density_layer(&poly, field, hatch, *angle, *threshold));
+    }
+    lines
+}
+
+/// Return the lines of one layer of `hatch`, drawn where the brightness is
+/// lower than `threshold`, or everywhere when it is 1.
+fn density_layer<F: Scalar2>(
+    poly: &geo::Polygon,
+    field: &F,
+    hatch: &DensityHatch,
+    angle: Angle,
+    threshold: f64,
+) -> Vec<LineString> {
+    let (dark, light) = hatch.spacing;
+    // Each level doubles the spacing of the previous one
+    let levels = (light / dark).log2().max(0.);
+    let drawn = |index: usize, pos: Vec2| {
+        let brightness = hatch.brightness.sample(field, pos);
+        let level = (brightness * levels).round() as u32;
+        (brightness < threshold || threshold >= 1.) && index.is_multiple_of(1 << level)
+    };
+    let mut lines = vec![];
+    for (index, segments) in scanlines(poly, angle, dark).into_iter().enumerate() {
+        for (start, end) in segments {
+            // Sample the field along the segment, keeping the drawn stretches
+            let steps = (start.distance(end) / dark).ceil().max(1.) as usize;
+            let mut from = None;
+            for k in 0..steps {
+                let a = start.lerp(end, k as f64 / steps as f64);
+                let b = start.lerp(end, (k + 1) as f64 / steps as f64);
+                match (drawn(index, (a + b) / 2.), from) {
+                    (true, None) => from = Some(a),
+                    (false, Some(f)) => {
+                        lines.push(LineString::line(f, a));
+                        from = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(f) = from {
+                lines.push(LineString::line(f, end));
+            }
+        }
+    }
+    lines
+}
+
+/// The space-filling curves used as fills.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Curve {
+    Hilbert,
+    Peano,
+    Gosper,
+}
+
+// The highest order of the curves, bounding the number of points
+const MAX_HILBERT_ORDER: u32 = 10;
+const MAX_PEANO_ORDER: u32 = 7;
+const MAX_GOSPER_ORDER: u32 = 7;
+
+/// Cover `shape` with a single space-filling `curve`, whose neighbouring
+/// stretches are `spacing` apart, and clip it to the shape. The order of
+/// the curve is the lowest one covering the shape.
+pub(crate) fn space_filling<T: ToGeoPolygon>(
+    shape: &T,
+    curve: Curve,
+    spacing: f64,
+) -> Vec<LineString> {
+    let Some(bbox) = shape.to_geo_polygon().bounding_rect() else {
+        return vec![];
+    };
+    if spacing <= 0. {
+        return vec![];
+    }
+    let center = Vec2::new(bbox.center().x, bbox.center().y);
+    let corners = [bbox.min(), bbox.max()].map(|c| Vec2::new(c.x, c.y));
+    let cells = f64::max(bbox.width(), bbox.height()) / spacing;
+    let points = match curve {
+        Curve::Hilbert => {
+            let order = (cells.log2().ceil().max(1.) as u32).min(MAX_HILBERT_ORDER);
+            grid_curve(hilbert(order), 1 << order, center, spacing)
+        }
+        Curve::Peano => {
+            let order = (cells.log(3.).ceil().max(1.) as u32).min(MAX_PEANO_ORDER);
+            grid_curve(peano(order), 3_i64.pow(order), center, spacing)
+        }
+        Curve::Gosper => {
+            // The Gosper island has a ragged outline: take the first order
+            // passing near every point of a grid over the bounding box
+            let (columns, rows) = (
+                (bbox.width() / spacing).ceil() as usize,
+                (bbox.height() / spacing).ceil() as usize,
+            );
+            let samples = (0..=columns)
+                .flat_map(|i| (0..=rows).map(move |j| (i, j)))
+                .map(|(i, j)| corners[0] + Vec2::new(i as f64, j as f64) * spacing)
+                .collect::<Vec<Vec2>>();
+            let mut points = vec![];
+            for order in 1..=MAX_GOSPER_ORDER {
+                let unit = gosper(order);
+                let middle = unit.iter().copied().sum::<Vec2>() / unit.len() as f64;
+                points = unit
+                    .iter()
+                    .map(|p| (*p - middle) * spacing + center)
+                    .collect::<Vec<Vec2>>();
+                let tree = RTree::bulk_load(points.iter().map(|p| [p.x, p.y]).collect());
+                let covered = samples.iter().all(|s| {
+                    tree.nearest_neighbor(&[s.x, s.y])
+                        .is_some_and(|p| Vec2::new(p[0], p[1]).distance(*s) <= spacing * 0.75)
+                });
+                if covered {
+                    break;
+                }
+            }
+            points
+        }
+    };
+    LineString::new(points).clip(shape, false)
+}
+
+/// Place the points of a curve running through the cells of a `cells` by
+/// `cells` grid, so that the grid is centered on `center`.
+fn grid_curve(curve: Vec<(i64, i64)>, cells: i64, center: Vec2, spacing: f64) -> Vec<Vec2> {
+    let origin = center - (cells as f64 - 1.) * spacing / 2.;
+    curve
+        .into_iter()
+        .map(|(x, y)| origin + Vec2::new(x as f64, y as f64) * spacing)
+        .collect()
+}
+
+/// Return the cells of a `2^order` by `2^order` grid in Hilbert curve order.
+fn hilbert(order: u32) -> Vec<(i64, i64)> {
+    let n = 1_i64 << order;
+    (0..n * n)
+        .map(|d| {
+            let (mut x, mut y, mut t) = (0, 0, d);
+            let mut s = 1;
+            while s < n {
+                let rx = 1 & (t / 2);
+                let ry = 1 & (t ^ rx);
+                if ry == 0 {
+                    if rx == 1 {
+                        x = s - 1 - x;
+                        y = s - 1 - y;
+                    }
+                    std::mem::swap(&mut x, &mut y);
+                }
+                x += s * rx;
+                y += s * ry;
+                t /= 4;
+                s *= 2;
+            }
+            (x, y)
+        })
+        .collect()
+}
+
+/// Return the cells of a `3^order` by `3^order` grid in Peano curve order.
+fn peano(order: u32) -> Vec<(i64, i64)> {
+    let mut curve = vec![(0, 0)];
+    let mut size = 1;
+    for _ in 0..order {
+        let mut next = Vec::with_capacity(curve.len() * 9);
+        // Visit the nine copies column by column, snaking up and down, and
+        // mirror them so that each one starts next to where the previous ends
+        for i in 0..3 {
+            for k in 0..3 {
+                let j = if i % 2 == 0 { k } else { 2 - k };
+                next.extend(curve.iter().map(|&(x, y)| {
+                    let x = if j % 2 == 1 { size - 1 - x } else { x };
+                    let y = if i % 2 == 1 { size - 1 - y } else { y };
+                    (i * size + x, j * size + y)
+                }));
+            }
+        }
+        curve = next;
+        size *= 3;
+    }
+    curve
+}
+
+/// Return the points of the Gosper curve of `order`, with unit steps.
+fn gosper(order: u32) -> Vec<Vec2> {
+    let mut rules = String::from("XF");
+    for _ in 0..order {
+        rules = rules
+            .chars()
+            .map(|c| match c {
+                'X' => "X+YF++YF-FX--FXFX-YF+",
+                'Y' => "-FX+YFYF++YF+FX--FX-Y",
+                'F' => "F",
+                '+' => "+",
+                _ => "-",
+            })
+            .collect();
+    }
+    let mut position = Vec2::ZERO;
+    let mut angle = 0.;
+    let mut points = vec![position];
+    for c in rules.chars() {
+        match c {
+            '+' => angle += 60.,
+            '-' => angle -= 60.,
+            'F' => {
+                position += Vec2::from_polar(Angle::from_degrees(angle), 1.);
+                points.push(position);
+            }
+            _ => {}
+        }
+    }
+    points
+}
+
+/// The settings of a spiral whose radius wobbles with the brightness
+/// sampled from a field: the wobble is `pitch / 2` wide where the field is
+/// darkest, and vanishes where it is lightest.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpiralHatch {
+    /// The distance between consecutive turns
+    pub pitch: f64,
+    /// The length of one wobble along the spiral
+    pub wavelength: f64,
+    /// The brightness flattening the wobble
+    pub brightness: Brightness,
+}
+
+impl SpiralHatch {
+    pub fn new(pitch: f64) -> Self {
+        Self {
+            pitch,
+            wavelength: pitch,
+            brightness: Brightness::default(),
+        }
+    }
+
+    /// Set how the field values map to the brightness flattening the wobble.
+    pub fn with_brightness(mut self, brightness: Brightness) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Set the length of one wobble along the spiral.
+    pub fn with_wavelength(mut self, wavelength: f64) -> Self {
+        self.wavelength = wavelength;
+        self
+    }
+}
+
+/// Fill `shape` with an Archimedean spiral going outwards from its
+/// centroid, with turns `pitch` apart.
+pub(crate) fn spiral<T: ToGeoPolygon>(shape: &T, pitch: f64) -> Vec<LineString> {
+    spiral_with(shape, pitch, f64::INFINITY, |_| 0.)
+}
+
+/// Fill `shape` with a spiral whose radius wobbles following `field`.
+pub(crate) fn density_spiral<T: ToGeoPolygon, F: Scalar2>(
+    shape: &T,
+    field: &F,
+    hatch: &SpiralHatch,
+) -> Vec<LineString> {
+    spiral_with(shape, hatch.pitch, hatch.wavelength, |pos| {
+        (1. - hatch.brightness.sample(field, pos)) * hatch.pitch / 4.
+    })
+}
+
+/// Trace a spiral over `shape`, moving each point away from the center by
+/// `amplitude` at that point times a sine wave of `wavelength`, and clip it
+/// to the shape. The pieces shorter than `pitch`, grazing the outline, are
+/// dropped.
+fn spiral_with<T: ToGeoPolygon>(
+    shape: &T,
+    pitch: f64,
+    wavelength: f64,
+    amplitude: impl Fn(Vec2) -> f64,
+) -> Vec<LineString> {
+    let poly = shape.to_geo_polygon();
+    let Some(centroid) = poly.centroid() else {
+        return vec![];
+    };
+    if pitch <= 0. || wavelength <= 0. {
+        return vec![];
+    }
+    let center = Vec2::new(centroid.x(), centroid.y());
+    // The spiral ends one turn past the furthest point of the shape
+    let end = poly
+        .exterior()
+        .coords()
+        .map(|c| center.distance(Vec2::new(c.x, c.y)))
+        .fold(0., f64::max)
+        + pitch;
+    let tolerance = pitch / 100.;
+    let (mut theta, mut length) = (0., 0.);
+    let mut points = vec![];
+    loop {
+        let radius = pitch * theta / TAU;
+        if radius > end {
+            break;
+        }
+        let direction = Vec2::from_polar(Angle::from_radians(theta), 1.);
+        let wobble = amplitude(center + direction * radius) * (TAU * length / wavelength).sin();
+        points.push(center + direction * (radius + wobble));
+        // Keep the chords within tolerance, and sample each wobble 8 times
+        let r = radius.max(pitch);
+        let step = (2. * (1. - tolerance / r).acos()).min(wavelength / 8. / r);
+        theta += step;
+        length += step * r;
+    }
+    LineString::new(points)
+        .clip(shape, false)
+        .into_iter()
+        .filter(|l| {
+            l.points
+                .windows(2)
+                .map(|w| w[0].distance(w[1]))
+                .sum::<f64>()
+                >= pitch
+        })
+        .collect()
+}
+
+/// Return whether the segment from `a` to `b`, both on the boundary of
+/// `poly`, runs inside it along the boundary, rather than across the shape
+/// or outside of it.
+fn connects_inside(poly: &geo::Polygon, a: Vec2, b: Vec2, spacing: f64) -> bool {
+    let tolerance = spacing * 1e-6;
+    let steps = (a.distance(b) * 4. / spacing).ceil().max(2.);
+    (1..steps as usize).all(|i| {
+        let p = a.lerp(b, i as f64 / steps);
+        let point = geo::Point::new(p.x, p.y);
+        poly.euclidean_distance(&point) <= tolerance
+            && std::iter::once(poly.exterior())
+                .chain(poly.interiors())
+                .any(|ring| ring.euclidean_distance(&point) <= spacing)
+    })
+}
+
+/// Fill `shape` with rings following its outline, `pen.thickness` apart,
+/// from the outside in. Each ring is joined to the one it surrounds, so
+/// that a shape which never splits is drawn as a single path.
+pub(crate) fn concentric<T: ToGeoPolygon>(shape: &T, pen: &Pen) -> Vec<LineString> {
+    // The paths being drawn, and the index of the last ring they reached
+    let mut chains: Vec<(Vec<Vec2>, usize)> = vec![];
+    for level in 0.. {
+        let inset = pen.thickness * (level as f64 + 0.5);
+        let rings = shape
+            .offset(-inset, JoinStyle::default())
+            .iter()
+            .flat_map(|p| p.rings())
+            .collect::<Vec<LineString>>();
+        if rings.is_empty() {
+            break;
+        }
+        for ring in rings {
+            // Continue the closest path ending on the ring just outside,
+            // unless the jump would leave the band between the two rings
+            let closest = chains
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, last))| *last + 1 == level)
+                .map(|(i, (points, _))| {
+                    let (_, distance) = nearest_point(&ring.points, points[points.len() - 1]);
+                    (i, distance)
+                })
+                .filter(|(_, distance)| *distance <= pen.thickness * 2.)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match closest {
+                Some((i, _)) => {
+                    let end = chains[i].0[chains[i].0.len() - 1];
+                    chains[i].0.extend(start_at_nearest(&ring.points, end));
+                    chains[i].1 = level;
+                }
+                None => chains.push((ring.points, level)),
+            }
+        }
+    }
+    chains
+        .into_iter()
+        .map(|(points, _)| LineString::new(points))
+        .collect()
+}
+
+/// Return the index of the segment of `ring` closest to `p`, and its distance.
+fn nearest_point(ring: &[Vec2], p: Vec2) -> (usize, f64) {
+    (0..ring.len() - 1)
+        .map(|i| (i, p.distance_to_segment(ring[i], ring[i + 1])))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, p.distance(ring[0])))
+}
+
+/// Return the closed `ring` going round from its point closest to `p`.
+fn start_at_nearest(ring: &[Vec2], p: Vec2) -> Vec<Vec2> {
+    let (i, _) = nearest_point(ring, p);
+    let start = p.project_onto_segment(ring[i], ring[i + 1]);
+    let mut points = vec![start];
+    points.extend_from_slice(&ring[i + 1..]);
+    points.extend_from_slice(&ring[1..=i]);
+    points.push(start);
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field::Scalar2;
+    use crate::prelude::*;
+    use crate::traits::hatch::{Brightness, DensityHatch, SpiralHatch};
+    use crate::traits::{DensityFill, HatchFillStrategy, ToGeoLineString, ToGeoPolygon};
+    use approx::assert_relative_eq;
+    use geo::{BoundingRect, Contains, EuclideanDistance};
+    use rstest::rstest;
+    use std::f64::consts::SQRT_2;
+
+    #[test]
+    fn concentric_square_is_a_single_path() {
+        let pen = Pen::new("black", 1., 1.);
+        let square = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let lines = square.hatch_fill(&pen, HatchFillStrategy::Concentric);
+        assert_eq!(lines.len(), 1);
+        let points = &lines[0].points;
+        for p in points {
+            assert!(p.x >= 0.5 - 1e-9 && p.x <= 9.5 + 1e-9);
+            assert!(p.y >= 0.5 - 1e-9 && p.y <= 9.5 + 1e-9);
+        }
+        // Rings at 0.5, 1.5, 2.5, 3.5 and 4.5 from the sides, the last one
+        // shrunk to a point
+        let rings = [9., 7., 5., 3., 1.]
+            .iter()
+            .map(|side| side * 4.)
+            .sum::<f64>();
+        let length = points.windows(2).map(|w| w[0].distance(w[1])).sum::<f64>();
+        assert!(length > rings - 4. && length < rings + 4. * 1.5);
+    }
+
+    #[test]
+    fn concentric_fill_splits_with_the_shape() {
+        let pen = Pen::new("black", 1., 1.);
+        // Two squares joined by a bridge narrower than two rings
+        let dumbbell = Polygon::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 4.),
+            Vec2::new(20., 4.),
+            Vec2::new(20., 0.),
+            Vec2::new(30., 0.),
+            Vec2::new(30., 10.),
+            Vec2::new(20., 10.),
+            Vec2::new(20., 6.),
+            Vec2::new(10., 6.),
+            Vec2::new(10., 10.),
+            Vec2::new(0., 10.),
+        ]);
+        let lines = dumbbell.hatch_fill(&pen, HatchFillStrategy::Concentric);
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn horizontal_lines_in_a_circle() {
+        let pen = Pen::new("black", 1., 1.);
+        let circle = Circle::new(Vec2::new(0., 0.), 5.);
+        let lines = circle.hatch_fill(&pen, HatchFillStrategy::HorizontalLines);
+        // A single zig-zag path going through ten lines
+        assert_eq!(lines.len(), 1);
+        let points = &lines[0].points;
+        assert_eq!(points.len(), 20);
+        assert_relative_eq!(points[10].y, 0.5, epsilon = 1e-2);
+        assert_relative_eq!(
+            points[10].distance(points[11]),
+            2. * 24.75_f64.sqrt(),
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn angled_lines() {
+        let pen = Pen::new("black", 0.1, 0.1);
+        let square = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let strategy = HatchFillStrategy::Lines {
+            angle: Angle::from_degrees(45.),
+            spacing: Some(SQRT_2),
+        };
+        let lines = square.hatch_fill(&pen, strategy);
+        assert_eq!(lines.len(), 1);
+        let points = &lines[0].points;
+        assert_eq!(points.len(), 20);
+        for pair in points.chunks(2) {
+            let d = pair[1] - pair[0];
+            assert_relative_eq!(d.x.abs(), d.y.abs(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn zig_zag_stays_inside() {
+        let pen = Pen::new("black", 1., 1.);
+        // A U shape, whose arms can't be joined at the top
+        let u = Polygon::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(4., 0.),
+            Vec2::new(4., 6.),
+            Vec2::new(6., 6.),
+            Vec2::new(6., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 10.),
+            Vec2::new(0., 10.),
+        ]);
+        let strategy = HatchFillStrategy::CrossHatch {
+            angles: (Angle::zero(), Angle::from_degrees(90.)),
+            spacing: None,
+        };
+        let lines = u.hatch_fill(&pen, strategy);
+        assert!(lines.len() >= 3);
+        for line in lines {
+            for pair in line.points.windows(2) {
+                let middle = (pair[0] + pair[1]) / 2.;
+                assert!(!(middle.x > 4. && middle.x < 6. && middle.y < 6.));
+            }
+        }
+    }
+
+    struct Gradient;
+
+    impl Scalar2 for Gradient {
+        fn number2(&self, pos: Vec2) -> f64 {
+            pos.x / 100.
+        }
+
+        fn angle2(&self, _pos: Vec2, _increments: f64) -> Angle {
+            Angle::zero()
+        }
+    }
+
+    // Count the lines crossing the vertical line at `x`.
+    fn crossing(lines: &[LineString], x: f64) -> usize {
+        lines
+            .iter()
+            .filter(|l| {
+                let (a, b) = (l.points[0], l.points[l.points.len() - 1]);
+                a.x.min(b.x) < x && a.x.max(b.x) > x
+            })
+            .count()
+    }
+
+    #[test]
+    fn density_follows_the_field() {
+        let square = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let hatch = DensityHatch::new(Angle::zero(), 1., 4.);
+        let lines = square.density_fill(&Gradient, &hatch);
+        assert_eq!(crossing(&lines, 10.), 100);
+        assert_eq!(crossing(&lines, 50.), 50);
+        assert_eq!(crossing(&lines, 90.), 25);
+
+        let hatch = hatch.with_layer(0.5, Angle::from_degrees(90.));
+        let layered = square.density_fill(&Gradient, &hatch);
+        let vertical = layered.len() - lines.len();
+        assert!(vertical > 0);
+        assert!(layered[lines.len()..]
+            .iter()
+            .all(|l| l.points.iter().all(|p| p.x <= 50. + 1e-9)));
+    }
+
+    #[rstest]
+    #[case(HatchFillStrategy::Hilbert, 64)]
+    #[case(HatchFillStrategy::Peano, 81)]
+    fn grid_curves_are_continuous(#[case] strategy: HatchFillStrategy, #[case] points: usize) {
+        let pen = Pen::new("black", 1., 1.);
+        let side = (points as f64).sqrt();
+        let square = Rect::new(Vec2::new(0., 0.), side, side);
+        let lines = square.hatch_fill(&pen, strategy);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].points.len(), points);
+        for pair in lines[0].points.windows(2) {
+            assert_relative_eq!(pair[0].distance(pair[1]), 1., epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn gosper_covers_a_circle() {
+        let pen = Pen::new("black", 1., 1.);
+        let circle = Circle::new(Vec2::new(5., 5.), 10.);
+        let lines = circle.hatch_fill(&pen, HatchFillStrategy::Gosper);
+        let points = lines.iter().flat_map(|l| l.points.iter());
+        assert!(points
+            .clone()
+            .all(|p| p.distance(circle.center) <= 10. + 1e-9));
+        for x in -4..=4 {
+            for y in -4..=4 {
+                let p = circle.center + Vec2::new(x as f64 * 2., y as f64 * 2.);
+                if p.distance(circle.center) < 9. {
+                    assert!(points.clone().any(|q| q.distance(p) <= 1.));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_in_a_circle() {
+        let pen = Pen::new("black", 1., 1.);
+        let circle = Circle::new(Vec2::new(5., 5.), 10.);
+        let lines = circle.hatch_fill(&pen, HatchFillStrategy::Spiral);
+        // Past the first path, only bits along the chords of the outline
+        let points = &lines[0].points;
+        assert!(lines[1..]
+            .iter()
+            .all(|l| l.points.iter().all(|p| p.distance(circle.center) > 9.98)));
+        assert_relative_eq!(points[0].x, 5., epsilon = 1e-3);
+        assert_relative_eq!(points[0].y, 5., epsilon = 1e-3);
+        assert_relative_eq!(
+            points[points.len() - 1].distance(circle.center),
+            10.,
+            epsilon = 0.02
+        );
+        // The turns cross the horizontal half line one pitch apart
+        let mut crossings = points
+            .windows(2)
+            .filter(|w| w[0].x > 5. && (w[0].y < 5.) != (w[1].y < 5.))
+            .map(|w| w[0].distance(circle.center))
+            .collect::<Vec<f64>>();
+        crossings.sort_by(f64::total_cmp);
+        assert_eq!(crossings.len(), 9);
+        for pair in crossings.windows(2) {
+            assert_relative_eq!(pair[1] - pair[0], 1., epsilon = 0.05);
+        }
+    }
+
+    // Return whether every point of a grid over `shape` lies within
+    // `distance` of the lines.
+    fn covers<T: ToGeoPolygon>(shape: &T, lines: &[LineString], distance: f64) -> bool {
+        let poly = shape.to_geo_polygon();
+        let bbox = poly.bounding_rect().unwrap();
+        let (columns, rows) = (bbox.width().ceil() as usize, bbox.height().ceil() as usize);
+        (0..=columns)
+            .flat_map(|i| (0..=rows).map(move |j| (i, j)))
+            .map(|(i, j)| geo::Point::new(bbox.min().x + i as f64, bbox.min().y + j as f64))
+            .filter(|p| poly.contains(p))
+            .all(|p| {
+                lines
+                    .iter()
+                    .any(|l| l.to_geo_linestring().euclidean_distance(&p) <= distance)
+            })
+    }
+
+    #[test]
+    fn spiral_covers_a_long_rectangle() {
+        let pen = Pen::new("black", 1., 1.);
+        let rect = Rect::new(Vec2::new(0., 0.), 100., 10.);
+        let lines = rect.hatch_fill(&pen, HatchFillStrategy::Spiral);
+        assert!(covers(&rect, &lines, 1.));
+    }
+
+    #[test]
+    fn spiral_covers_around_holes() {
+        let pen = Pen::new("black", 1., 1.);
+        let frame = Polygon::with_interiors(
+            Rect::new(Vec2::new(0., 0.), 30., 20.).to_polygon().points,
+            vec![Rect::new(Vec2::new(10., 5.), 10., 10.).to_polygon().points],
+        );
+        let lines = frame.hatch_fill(&pen, HatchFillStrategy::Spiral);
+        assert!(covers(&frame, &lines, 1.));
+        assert!(lines.iter().flat_map(|l| l.points.iter()).all(|p| {
+            p.x <= 10. + 1e-9 || p.x >= 20. - 1e-9 || p.y <= 5. + 1e-9 || p.y >= 15. - 1e-9
+        }));
+    }
+
+    #[test]
+    fn spiral_wobbles_where_the_field_is_dark() {
+        let length = |l: &LineString| {
+            l.points
+                .windows(2)
+                .map(|w| w[0].distance(w[1]))
+                .sum::<f64>()
+        };
+        let square = Rect::new(Vec2::new(0., 0.), 100., 100.);
+        let hatch = SpiralHatch::new(2.);
+        let light = square.density_spiral(
+            &Gradient,
+            &hatch.clone().with_brightness(Brightness::new(-1., -0.5)),
+        );
+        let dark =
+            square.density_spiral(&Gradient, &hatch.with_brightness(Brightness::new(2., 3.)));
+        let plain = square.hatch_fill(&Pen::new("black", 2., 1.), HatchFillStrategy::Spiral);
+        assert_relative_eq!(
+            light.iter().map(length).sum::<f64>(),
+            plain.iter().map(length).sum::<f64>(),
+            max_relative = 1e-3
+        );
+        assert!(dark.iter().map(length).sum::<f64>() > 1.3 * light.iter().map(length).sum::<f64>());
+    }
+}
@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::shapes::arc::segments_for_tolerance;
+use crate::shapes::polygon::Polygon;
+use crate::traits::ToGeoPolygon;
+use crate::vec2::Vec2;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+// Sine of the angle under which consecutive edges are considered collinear
+const PARALLEL: f64 = 1e-9;
+// Maximum deviation of round joins, relative to the distance
+const ROUND_TOLERANCE: f64 = 1e-3;
+// Distance, relative to the size of the shape, under which points are merged
+const SNAP: f64 = 1e-10;
+// Distance, relative to the size of the shape, at which the sides of an edge are probed
+const PROBE: f64 = 1e-7;
+// Area, relative to the size of the shape squared, under which a ring is dropped
+const SLIVER: f64 = 1e-10;
+
+/// How the offset edges are connected around convex corners.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum JoinStyle {
+    /// Extend the edges until they meet, falling back to `Bevel` when the
+    /// corner would stick out more than the given multiple of the distance
+    Miter(f64),
+    /// Connect the edges with a circular arc
+    Round,
+    /// Connect the edges with a straight segment
+    Bevel,
+}
+
+impl Default for JoinStyle {
+    fn default() -> Self {
+        JoinStyle::Miter(4.)
+    }
+}
+
+pub trait Offset {
+    /// Return the polygons bounded by the curves lying at `distance` from
+    /// the boundary: outside it when `distance` is positive, inside it when
+    /// negative, whatever the winding of the rings.
+    ///
+    /// Shrinking may split the shape into several polygons, or make it
+    /// vanish, in which case the result is empty.
+    fn offset(&self, distance: f64, join: JoinStyle) -> Vec<Polygon>;
+}
+
+impl<S: ToGeoPolygon> Offset for S {
+    fn offset(&self, distance: f64, join: JoinStyle) -> Vec<Polygon> {
+        let poly = self.to_geo_polygon();
+        let ring = |ring: &geo::LineString, exterior: bool| {
+            let mut points = ring
+                .coords()
+                .map(|c| Vec2::new(c.x, c.y))
+                .collect::<Vec<Vec2>>();
+            points.dedup();
+            if points.len() > 1 && points[0] == points[points.len() - 1] {
+                points.pop();
+            }
+            // The area is on the left of every ring
+            if (signed_area(&points) > 0.) != exterior {
+                points.reverse();
+            }
+            points
+        };
+        let rings = std::iter::once(ring(poly.exterior(), true))
+            .chain(poly.interiors().iter().map(|r| ring(r, false)))
+            .filter(|r| r.len() > 2)
+            .map(|r| {
+                if distance == 0. {
+                    r
+                } else {
+                    offset_ring(&r, distance, join)
+                }
+            })
+            .collect::<Vec<Vec<Vec2>>>();
+        // The raw offset rings may cross themselves and each other: the
+        // result is made of the points they wind around positively.
+        positive_area(&rings)
+    }
+}
+
+/// Twice the area enclosed by `ring`, positive when it turns left, i.e.
+/// counterclockwise with the y axis pointing upwards.
+pub(crate) fn signed_area(ring: &[Vec2]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let (p1, p2) = (ring[i], ring[(i + 1) % ring.len()]);
+            p1.x * p2.y - p2.x * p1.y
+        })
+        .sum()
+}
+
+fn length(a: Vec2) -> f64 {
+    a.dot(a).sqrt()
+}
+
+/// Move every edge of `ring`, which has its area on the left, by `distance`
+/// towards its right, and connect the moved edges.
+fn offset_ring(ring: &[Vec2], distance: f64, join: JoinStyle) -> Vec<Vec2> {
+    let n = ring.len();
+    let radius = distance.abs();
+    let normal = |a: Vec2, b: Vec2| {
+        let e = b - a;
+        Vec2::new(e.y, -e.x) * (distance / length(e))
+    };
+    let mut points = vec![];
+    for i in 0..n {
+        let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let (n1, n2) = (normal(a, b), normal(b, c));
+        let (e1, e2) = (b - a, c - b);
+        let sin = e1.cross(e2) / (length(e1) * length(e2));
+        let forward = e1.dot(e2) > 0.;
+        if sin.abs() < PARALLEL && forward {
+            points.push(b + n1);
+            continue;
+        }
+        // Left turns are convex corners, which open a gap when growing
+        let convex = if sin.abs() < PARALLEL {
+            distance > 0.
+        } else {
+            sin * distance > 0.
+        };
+        if !convex {
+            // The detour through the vertex cancels out in the winding
+            points.extend([b + n1, b, b + n2]);
+            continue;
+        }
+        let cos = (n1.dot(n2) / (radius * radius)).clamp(-1., 1.);
+        match join {
+            JoinStyle::Miter(limit) if cos > -1. && (2. / (1. + cos)).sqrt() <= limit => {
+                points.push(b + (n1 + n2) / (1. + cos));
+            }
+            JoinStyle::Round => {
+                let angle = cos.acos();
+                // Turn from the first normal towards the edge direction
+                let sign = n1.cross(e1).signum();
+                let steps = segments_for_tolerance(radius, angle, radius * ROUND_TOLERANCE);
+                points.extend((0..=steps).map(|k| {
+                    let (sin, cos) = (sign * angle * k as f64 / steps as f64).sin_cos();
+                    b + Vec2::new(n1.x * cos - n1.y * sin, n1.x * sin + n1.y * cos)
+                }));
+            }
+            _ => points.extend([b + n1, b + n2]),
+        }
+    }
+    points
+}
+
+/// Return +1 or -1 when the ray going from `p` towards increasing x
+/// crosses the segment from `a` to `b` upwards or downwards, 0 otherwise.
+fn crossing(a: Vec2, b: Vec2, p: Vec2) -> i32 {
+    if a.y <= p.y {
+        if b.y > p.y && (b - a).cross(p - a) > 0. {
+            return 1;
+        }
+    } else if b.y <= p.y && (b - a).cross(p - a) < 0. {
+        return -1;
+    }
+    0
+}
+
+fn ring_winding(ring: &[Vec2], p: Vec2) -> i32 {
+    (0..ring.len())
+        .map(|i| crossing(ring[i], ring[(i + 1) % ring.len()], p))
+        .sum()
+}
+
+type Segment = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// Return the intersections of the segments from `p1` to `p2` and from `q1`
+/// to `q2`, as the parameters along each segment and the point itself.
+/// Points closer than `snap` to an endpoint are moved onto it.
+fn intersections(p1: Vec2, p2: Vec2, q1: Vec2, q2: Vec2, snap: f64) -> Vec<(f64, f64, Vec2)> {
+    let (r, s, qp) = (p2 - p1, q2 - q1, q1 - p1);
+    let (lr, ls) = (length(r), length(s));
+    let denom = r.cross(s);
+    if denom.abs() <= PARALLEL * lr * ls {
+        if qp.cross(r).abs() / lr > snap {
+            return vec![];
+        }
+        // Overlapping segments are split at each other's endpoints
+        let along_p = |q: Vec2| (q - p1).dot(r) / (lr * lr);
+        let along_q = |p: Vec2| (p - q1).dot(s) / (ls * ls);
+        let mut result = vec![];
+        for q in [q1, q2] {
+            let t = along_p(q);
+            if t > 0. && t < 1. {
+                result.push((t, along_q(q), q));
+            }
+        }
+        for p in [p1, p2] {
+            let u = along_q(p);
+            if u > 0. && u < 1. {
+                result.push((along_p(p), u, p));
+            }
+        }
+        return result;
+    }
+    let t = qp.cross(s) / denom;
+    let u = qp.cross(r) / denom;
+    let (tt, tu) = (snap / lr, snap / ls);
+    if t < -tt || t > 1. + tt || u < -tu || u > 1. + tu {
+        return vec![];
+    }
+    let point = if t <= tt {
+        p1
+    } else if t >= 1. - tt {
+        p2
+    } else if u <= tu {
+        q1
+    } else if u >= 1. - tu {
+        q2
+    } else {
+        p1 + r * t
+    };
+    vec![(t.clamp(0., 1.), u.clamp(0., 1.), point)]
+}
+
+/// Return the polygons covering the points around which `rings` wind
+/// positively.
+fn positive_area(rings: &[Vec<Vec2>]) -> Vec<Polygon> {
+    let segments = rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(|i| (ring[i], ring[(i + 1) % ring.len()])))
+        .filter(|(a, b)| a != b)
+        .collect::<Vec<(Vec2, Vec2)>>();
+    let (min, max) = segments.iter().fold(
+        (Vec2::new(f64::MAX, f64::MAX), Vec2::new(f64::MIN, f64::MIN)),
+        |(min, max), (a, _)| {
+            (
+                Vec2::new(min.x.min(a.x), min.y.min(a.y)),
+                Vec2::new(max.x.max(a.x), max.y.max(a.y)),
+            )
+        },
+    );
+    let extent = f64::max(max.x - min.x, max.y - min.y);
+    if segments.is_empty() || extent <= 0. {
+        return vec![];
+    }
+    let (snap, probe) = (extent * SNAP, extent * PROBE);
+    let tree = RTree::bulk_load(
+        segments
+            .iter()
+            .enumerate()
+            .map(|(i, (a, b))| Segment::new(Rectangle::from_corners([a.x, a.y], [b.x, b.y]), i))
+            .collect(),
+    );
+
+    // Split the segments wherever they meet
+    let mut splits = segments
+        .iter()
+        .map(|(a, b)| vec![(0., *a), (1., *b)])
+        .collect::<Vec<Vec<(f64, Vec2)>>>();
+    for (i, (p1, p2)) in segments.iter().enumerate() {
+        let envelope = AABB::from_corners(
+            [p1.x.min(p2.x) - snap, p1.y.min(p2.y) - snap],
+            [p1.x.max(p2.x) + snap, p1.y.max(p2.y) + snap],
+        );
+        for other in tree.locate_in_envelope_intersecting(&envelope) {
+            let j = other.data;
+            if j <= i {
+                continue;
+            }
+            let (q1, q2) = segments[j];
+            for (t, u, point) in intersections(*p1, *p2, q1, q2, snap) {
+                splits[i].push((t, point));
+                splits[j].push((u, point));
+            }
+        }
+    }
+
+    let winding = |p: Vec2| -> i32 {
+        let ray = AABB::from_corners([p.x, p.y], [max.x + 1., p.y]);
+        tree.locate_in_envelope_intersecting(&ray)
+            .map(|s| {
+                let (a, b) = segments[s.data];
+                crossing(a, b, p)
+            })
+            .sum()
+    };
+    let key = |p: Vec2| ((p.x / snap).round() as i64, (p.y / snap).round() as i64);
+
+    // Keep the pieces with the positive area on one side only, directed so
+    // that it lies on their left
+    let mut seen = HashSet::new();
+    let mut edges = vec![];
+    for (i, split) in splits.iter_mut().enumerate() {
+        split.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for pair in split.windows(2) {
+            let (a, b) = (pair[0].1, pair[1].1);
+            let (ka, kb) = (key(a), key(b));
+            if ka == kb || !seen.insert((ka.min(kb), ka.max(kb))) {
+                continue;
+            }
+            let mid = (a + b) / 2.;
+            // Probe closer than the other segments passing by, which may run
+            // along this one at a tiny angle
+            let around = AABB::from_corners(
+                [mid.x - probe, mid.y - probe],
+                [mid.x + probe, mid.y + probe],
+            );
+            let probe = tree
+                .locate_in_envelope_intersecting(&around)
+                .filter(|s| s.data != i)
+                .map(|s| mid.distance_to_segment(segments[s.data].0, segments[s.data].1))
+                .filter(|d| *d > 0.)
+                .fold(probe, |probe, d| probe.min(d / 2.));
+            let e = b - a;
+            let left = Vec2::new(-e.y, e.x) * (probe / length(e));
+            let inside_left = winding(mid + left) > 0;
+            let inside_right = winding(mid - left) > 0;
+
+            if inside_left && !inside_right {
+                edges.push((ka, kb, a));
+            } else if inside_right && !inside_left {
+                edges.push((kb, ka, b));
+            }
+        }
+    }
+
+    // Drop the pieces leading nowhere, so that they cannot stop a ring from
+    // closing, until every piece starts where another one ends
+    loop {
+        let starts = edges.iter().map(|e| e.0).collect::<HashSet<(i64, i64)>>();
+        let ends = edges.iter().map(|e| e.1).collect::<HashSet<(i64, i64)>>();
+        let count = edges.len();
+        edges.retain(|e| starts.contains(&e.1) && ends.contains(&e.0));
+        if edges.len() == count {
+            break;
+        }
+    }
+
+    // Link the pieces into rings, turning left as much as possible where
+    // several rings touch, so that each ring encloses a single area
+    let mut outgoing: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.0).or_default().push(i);
+    }
+    let position = |k: (i64, i64)| Vec2::new(k.0 as f64 * snap, k.1 as f64 * snap);
+    let mut used = vec![false; edges.len()];
+    let mut exteriors = vec![];
+    let mut holes = vec![];
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut ring = vec![edges[first].2];
+        let mut current = first;
+        let closed = loop {
+            let (from, to, _) = edges[current];
+            if to == edges[first].0 {
+                break true;
+            }
+            let direction = position(to) - position(from);
+            let next = outgoing.get(&to).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|&&c| !used[c])
+                    .max_by(|&&c1, &&c2| {
+                        let turn = |c: usize| {
+                            let d = position(edges[c].1) - position(to);
+                            direction.cross(d).atan2(direction.dot(d))
+                        };
+                        turn(c1).total_cmp(&turn(c2))
+                    })
+                    .copied()
+            });
+            match next {
+                Some(next) => {
+                    used[next] = true;
+                    ring.push(edges[next].2);
+                    current = next;
+                }
+                None => break false,
+            }
+        };
+        let area = signed_area(&ring);
+        if !closed || area.abs() < extent * extent * SLIVER {
+            continue;
+        }
+        if area > 0. {
+            exteriors.push(ring);
+        } else {
+            holes.push(ring);
+        }
+    }
+
+    // Each hole belongs to the smallest exterior ring around it
+    let mut interiors = vec![vec![]; exteriors.len()];
+    for hole in holes {
+        let e = hole[1] - hole[0];
+        let p = (hole[0] + hole[1]) / 2. + Vec2::new(-e.y, e.x) * (probe / length(e));
+        let owner = (0..exteriors.len())
+            .filter(|&i| ring_winding(&exteriors[i], p) != 0)
+            .min_by(|&i, &j| signed_area(&exteriors[i]).total_cmp(&signed_area(&exteriors[j])));
+        if let Some(owner) = owner {
+            interiors[owner].push(hole);
+        }
+    }
+    exteriors
+        .into_iter()
+        .zip(interiors)
+        .map(|(exterior, interiors)| Polygon::with_interiors(exterior, interiors))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::traits::offset::{JoinStyle, Offset};
+    use crate::traits::{BoundingBox, ToGeoPolygon};
+    use approx::assert_relative_eq;
+    use geo::Area;
+    use rstest::rstest;
+
+    fn area(polygons: &[Polygon]) -> f64 {
+        polygons
+            .iter()
+            .map(|p| p.to_geo_polygon().unsigned_area())
+            .sum()
+    }
+
+    #[rstest]
+    #[case(JoinStyle::Miter(4.), 196.)]
+    #[case(JoinStyle::Bevel, 196. - 4. * 2.)]
+    #[case(JoinStyle::Round, 180. + 4. * PI)]
+    fn grow_a_square(#[case] join: JoinStyle, #[case] expected: f64) {
+        let square = Rect::new(Vec2::new(0., 0.), 10., 10.);
+        let grown = square.offset(2., join);
+        assert_eq!(grown.len(), 1);
+        assert_relative_eq!(area(&grown), expected, max_relative = 1e-3);
+    }
+
+    #[test]
+    fn winding_does_not_matter() {
+        let square = Rect::new(Vec2::new(0., 0.), 10., 10.).to_polygon();
+        let mut reversed = square.clone();
+        reversed.points.reverse();
+        for polygon in [square, reversed] {
+            let shrunk = polygon.offset(-1., JoinStyle::default());
+            let bbox = shrunk[0].to_linestring().bbox();
+            assert_relative_eq!(bbox.xy.x, 1., epsilon = 1e-9);
+            assert_relative_eq!(bbox.width, 8., epsilon = 1e-9);
+        }
+    }
+
+    #[rstest]
+    #[case(JoinStyle::default())]
+    #[case(JoinStyle::Round)]
+    #[case(JoinStyle::Bevel)]
+    fn nearly_collinear_reflex_vertex(#[case] join: JoinStyle) {
+        // The edges moved from (74.70, 112.18) cross at a tiny angle
+        let polygon = Polygon::new(vec![
+            Vec2::new(123.99, 100.),
+            Vec2::new(113.66, 117.13),
+            Vec2::new(90.72, 140.67),
+            Vec2::new(74.70, 112.18),
+            Vec2::new(55.71, 78.67),
+            Vec2::new(91.38, 62.21),
+            Vec2::new(118.21, 77.17),
+        ]);
+        let area = polygon.to_geo_polygon().unsigned_area();
+        for distance in [0.5, 1., 1.5, 2.] {
+            let grown = polygon.offset(distance, join);
+            assert_eq!(grown.len(), 1);
+            assert!(grown[0].interiors.is_empty());
+            assert!(grown[0].to_geo_polygon().unsigned_area() > area);
+        }
+        assert!(polygon.scale_dist(-1.).is_some());
+    }
+
+    #[test]
+    fn concave_and_parallel_edges() {
+        // An L shape, with collinear points along its edges
+        let l = Polygon::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(5., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 4.),
+            Vec2::new(4., 4.),
+            Vec2::new(4., 10.),
+            Vec2::new(0., 10.),
+        ]);
+        let grown = l.offset(1., JoinStyle::Miter(4.));
+        assert_relative_eq!(area(&grown), 12. * 6. + 6. * 6., epsilon = 1e-6);
+        let shrunk = l.offset(-1., JoinStyle::Miter(4.));
+        assert_relative_eq!(area(&shrunk), 8. * 2. + 2. * 6., epsilon = 1e-6);
+    }
+
+    #[test]
+    fn shrinking_splits_and_vanishes() {
+        // Two squares joined by a thin bridge
+        let dumbbell = Polygon::new(vec![
+            Vec2::new(0., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 4.),
+            Vec2::new(20., 4.),
+            Vec2::new(20., 0.),
+            Vec2::new(30., 0.),
+            Vec2::new(30., 10.),
+            Vec2::new(20., 10.),
+            Vec2::new(20., 6.),
+            Vec2::new(10., 6.),
+            Vec2::new(10., 10.),
+            Vec2::new(0., 10.),
+        ]);
+        assert_eq!(dumbbell.offset(-1.5, JoinStyle::Bevel).len(), 2);
+        assert!(dumbbell.offset(-6., JoinStyle::Bevel).is_empty());
+    }
+
+    #[test]
+    fn holes_grow_when_shrinking() {
+        let frame = Polygon::with_interiors(
+            Rect::new(Vec2::new(0., 0.), 30., 30.).to_polygon().points,
+            vec![Rect::new(Vec2::new(10., 10.), 10., 10.).to_polygon().points],
+        );
+        let shrunk = frame.offset(-2., JoinStyle::default());
+        assert_eq!(shrunk.len(), 1);
+        assert_eq!(shrunk[0].interiors.len(), 1);
+        assert_relative_eq!(area(&shrunk), 26. * 26. - 14. * 14., epsilon = 1e-6);
+        assert!(frame.offset(-6., JoinStyle::default()).is_empty());
+    }
+}
@@ -6,6 +6,8 @@ use crate::prelude::Vec2;
 const DPI: f64 = 96.0;
 // Same resolution but expressed in Dots per Millimeter
 const DPM: f64 = 3.779527;
+// Millimeters in one inch
+const MM_PER_IN: f64 = 25.4;
 
 #[derive(Clone, Copy)]
 pub enum Uom {
@@ -22,16 +24,78 @@ impl Uom {
             (Uom::Px, Uom::Px) => n,
             (Uom::Px, Uom::Mm) => n / DPM,
             (Uom::Px, Uom::In) => n / DPI,
-            (_, _) => unimplemented!(),
+            (Uom::Mm, Uom::Mm) => n,
+            (Uom::In, Uom::In) => n,
+            (Uom::Mm, Uom::In) => n / MM_PER_IN,
+            (Uom::In, Uom::Mm) => n * MM_PER_IN,
         }
     }
 
-    pub fn convert_vec2(v: Vec2, from: Uom, to: Uom) -> Vec2 {
-        match (from, to) {
-            (Uom::Mm, Uom::Px) => Vec2::new(v.x * DPM, v.y * DPM),
-            (Uom::In, Uom::Px) => Vec2::new(v.x * DPI, v.y * DPI),
-            (Uom::Px, Uom::Px) => v,
-            (_, _) => unimplemented!(),
+    /// Parse a CSS length like `0.5mm` or `2px` and return it in pixels.
+    /// Lengths without a unit are in pixels.
+    pub fn parse_css_length(css: &str) -> Option<f64> {
+        let css = css.trim();
+        // The number ends at the first character that cannot continue a
+        // float, an exponent being followed by a digit or a sign
+        let bytes = css.as_bytes();
+        let continues = |i: usize| match bytes[i] {
+            b'0'..=b'9' | b'.' | b'+' | b'-' => true,
+            b'e' | b'E' => bytes
+                .get(i + 1)
+                .is_some_and(|c| c.is_ascii_digit() || *c == b'+' || *c == b'-'),
+            _ => false,
+        };
+        let end = (0..bytes.len())
+            .find(|&i| !continues(i))
+            .unwrap_or(bytes.len());
+        let (number, uom) = css.split_at(end);
+        let number = number.parse::<f64>().ok()?;
+        match uom.trim() {
+            "" | "px" => Some(number),
+            "mm" => Some(Uom::convert_scalar(number, Uom::Mm, Uom::Px)),
+            "cm" => Some(Uom::convert_scalar(number * 10., Uom::Mm, Uom::Px)),
+            "in" => Some(Uom::convert_scalar(number, Uom::In, Uom::Px)),
+            "pt" => Some(number * DPI / 72.),
+            _ => None,
         }
     }
+
+    pub fn convert_vec2(v: Vec2, from: Uom, to: Uom) -> Vec2 {
+        Vec2::new(
+            Uom::convert_scalar(v.x, from, to),
+            Uom::convert_scalar(v.y, from, to),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::uom::Uom;
+    use approx::assert_relative_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("2px", 2.)]
+    #[case("2", 2.)]
+    #[case("1in", 96.)]
+    #[case("0.5mm", 1.8897635)]
+    #[case(" 72pt", 96.)]
+    #[case("2 px", 2.)]
+    #[case("1e-3mm", 0.0037795276)]
+    #[case("5e-1mm", 1.8897635)]
+    #[case("2E2px", 200.)]
+    #[case("1.5e+1", 15.)]
+    fn parse_css_length(#[case] css: &str, #[case] expected: f64) {
+        assert_relative_eq!(
+            Uom::parse_css_length(css).unwrap(),
+            expected,
+            epsilon = 0.00001
+        );
+    }
+
+    #[test]
+    fn parse_css_length_invalid() {
+        assert_eq!(Uom::parse_css_length("12em"), None);
+        assert_eq!(Uom::parse_css_length("mm"), None);
+    }
 }
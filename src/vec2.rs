@@ -34,7 +34,7 @@ impl Vec2 {
         let distance = self.distance(Vec2::ZERO);
         let signed = f64::atan2(self.y, self.x);
         let angle = if signed.is_sign_negative() {
-            Angle::from_radians(TAU - (-1. * signed))
+            Angle::from_radians(TAU + signed)
         } else {
             Angle::from_radians(signed)
         };
@@ -51,6 +51,39 @@ impl Vec2 {
         (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
     }
 
+    /// Compute the dot product of `self` and `rhs`.
+    pub(crate) fn dot(&self, rhs: Vec2) -> f64 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    /// Compute the z component of the cross product of `self` and `rhs`.
+    pub(crate) fn cross(&self, rhs: Vec2) -> f64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    /// Return the point of the segment from `a` to `b` closest to `self`.
+    pub(crate) fn project_onto_segment(&self, a: Vec2, b: Vec2) -> Self {
+        let ab = b - a;
+        let length_squared = ab.dot(ab);
+        if length_squared == 0. {
+            return a;
+        }
+        let t = ((*self - a).dot(ab) / length_squared).clamp(0., 1.);
+        a + ab * t
+    }
+
+    /// Compute the distance between `self` and the segment from `a` to `b`.
+    pub(crate) fn distance_to_segment(&self, a: Vec2, b: Vec2) -> f64 {
+        self.distance(self.project_onto_segment(a, b))
+    }
+
+    /// Check whether the ray from `self` towards positive x crosses the
+    /// segment from `a` to `b`, counting each end on one side only.
+    pub(crate) fn ray_crosses(&self, a: Vec2, b: Vec2) -> bool {
+        (a.y > self.y) != (b.y > self.y)
+            && self.x < a.x + (self.y - a.y) / (b.y - a.y) * (b.x - a.x)
+    }
+
     /// Rotate `self` around the origin by `angle`.
     pub fn rotate(&self, angle: Angle) -> Self {
         Vec2::new(
@@ -68,7 +101,7 @@ impl Vec2 {
     pub fn angle_between(&self, rhs: Vec2) -> Angle {
         let signed = f64::atan2(rhs.y - self.y, rhs.x - self.x);
         if signed.is_sign_negative() {
-            Angle::from_radians(TAU - (-1. * signed))
+            Angle::from_radians(TAU + signed)
         } else {
             Angle::from_radians(signed)
         }
@@ -241,6 +274,7 @@ impl Sum<Vec2> for Vec2 {
 }
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use crate::angle::Angle;
     use crate::traits::Lerp;
@@ -316,6 +350,47 @@ mod tests {
         assert_relative_eq!(v1.distance_squared(v2), expected, epsilon = EPSILON);
     }
 
+    #[rstest]
+    #[case(2., 1., 2., 0.)]
+    #[case(-3., 4., 0., 0.)]
+    #[case(7., -1., 4., 0.)]
+    #[case(2., 2., 2., 0.)]
+    fn project_onto_segment(
+        #[case] x: f64,
+        #[case] y: f64,
+        #[case] expected_x: f64,
+        #[case] expected_y: f64,
+    ) {
+        let p = Vec2::new(x, y);
+        let projected = p.project_onto_segment(Vec2::ZERO, Vec2::new(4., 0.));
+        assert_eq!(projected, Vec2::new(expected_x, expected_y));
+        assert_relative_eq!(
+            p.distance_to_segment(Vec2::ZERO, Vec2::new(4., 0.)),
+            p.distance(projected)
+        );
+    }
+
+    #[test]
+    fn project_onto_a_point() {
+        let a = Vec2::new(1., 1.);
+        assert_eq!(Vec2::new(4., 5.).project_onto_segment(a, a), a);
+        assert_relative_eq!(Vec2::new(4., 5.).distance_to_segment(a, a), 5.);
+    }
+
+    #[rstest]
+    #[case(0., 1., true)]
+    #[case(3., 1., false)]
+    #[case(0., 3., false)]
+    #[case(0., 0., true)]
+    #[case(0., 2., false)]
+    fn ray_crosses(#[case] x: f64, #[case] y: f64, #[case] expected: bool) {
+        let p = Vec2::new(x, y);
+        assert_eq!(
+            p.ray_crosses(Vec2::new(2., 0.), Vec2::new(2., 2.)),
+            expected
+        );
+    }
+
     #[rstest]
     #[case(0., 2., 90., -2., 0.)]
     #[case(0., 2., 180., 0., -2.)]